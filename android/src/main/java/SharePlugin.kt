@@ -15,15 +15,30 @@ import app.tauri.annotation.TauriPlugin
 import app.tauri.plugin.Invoke
 import app.tauri.plugin.Plugin
 import androidx.core.content.FileProvider
+import androidx.core.text.HtmlCompat
 import java.io.File
-import java.io.FileInputStream
-import java.io.FileOutputStream
 
 @InvokeArg
 class ShareTextOptions {
     lateinit var text: String
     var mimeType: String = "text/plain"
     var title: String? = null
+    var subject: String? = null
+}
+
+@InvokeArg
+class ShareHtmlOptions {
+    lateinit var html: String
+    lateinit var text: String
+    var title: String? = null
+    var subject: String? = null
+}
+
+@InvokeArg
+class ShareUrlOptions {
+    lateinit var url: String
+    var title: String? = null
+    var thumbnail: String? = null
 }
 
 @InvokeArg
@@ -33,6 +48,33 @@ class ShareFileOptions {
     var title: String? = null
 }
 
+@InvokeArg
+class SharedFile {
+    lateinit var url: String
+    var mimeType: String? = null
+}
+
+@InvokeArg
+class ShareFilesOptions {
+    lateinit var files: Array<SharedFile>
+    var title: String? = null
+}
+
+@InvokeArg
+class ShareItem {
+    lateinit var type: String
+    var text: String? = null
+    var url: String? = null
+    var mimeType: String? = null
+    var html: String? = null
+}
+
+@InvokeArg
+class ShareOptions {
+    lateinit var items: Array<ShareItem>
+    var title: String? = null
+}
+
 @TauriPlugin
 class SharePlugin(private val activity: Activity): Plugin(activity) {
     /**
@@ -47,6 +89,7 @@ class SharePlugin(private val activity: Activity): Plugin(activity) {
             this.type = args.mimeType
             this.putExtra(Intent.EXTRA_TEXT, args.text)
             this.putExtra(Intent.EXTRA_TITLE, args.title)
+            this.putExtra(Intent.EXTRA_SUBJECT, args.subject)
         }
 
         val shareIntent = Intent.createChooser(sendIntent, null)
@@ -56,41 +99,83 @@ class SharePlugin(private val activity: Activity): Plugin(activity) {
     @ActivityCallback
     private fun shareTextResult(invoke: Invoke, result: ActivityResult) {
         if (result.resultCode == Activity.RESULT_CANCELED) {
-            invoke.reject("Share cancelled")
+            invoke.reject("Share cancelled", "CANCELLED")
             return
         }
         invoke.resolve()
     }
 
     /**
-     * Open the native sharing interface to share a file
+     * Open the native sharing interface to share formatted text, with a plain-text alternative
      */
     @Command
-    fun shareFile(invoke: Invoke) {
-        val args = invoke.parseArgs(ShareFileOptions::class.java)
-        
-        // Get the source file from the URL
-        val sourceFile = if (args.url.startsWith("file://")) {
-            File(Uri.parse(args.url).path!!)
-        } else {
-            File(args.url)
+    fun shareHtml(invoke: Invoke) {
+        val args = invoke.parseArgs(ShareHtmlOptions::class.java)
+
+        // EXTRA_HTML_TEXT must be accompanied by a plain-text EXTRA_TEXT
+        val sendIntent = Intent().apply {
+            this.action = Intent.ACTION_SEND
+            this.type = "text/html"
+            this.putExtra(Intent.EXTRA_TEXT, args.text)
+            this.putExtra(Intent.EXTRA_HTML_TEXT, args.html)
+            this.putExtra(Intent.EXTRA_TITLE, args.title)
+            this.putExtra(Intent.EXTRA_SUBJECT, args.subject)
         }
-        
-        // Create a temporary file to store the data
-        val tempFile = File(activity.cacheDir, sourceFile.name)
-        
-        // Copy the source file to the temporary file
-        sourceFile.inputStream().use { input ->
-            tempFile.outputStream().use { output ->
-                input.copyTo(output)
-            }
+
+        val shareIntent = Intent.createChooser(sendIntent, null)
+        startActivityForResult(invoke, shareIntent, "shareHtmlResult")
+    }
+
+    @ActivityCallback
+    private fun shareHtmlResult(invoke: Invoke, result: ActivityResult) {
+        if (result.resultCode == Activity.RESULT_CANCELED) {
+            invoke.reject("Share cancelled", "CANCELLED")
+            return
         }
+        invoke.resolve()
+    }
 
-        // Get the authority from the app's manifest
-        val authority = "${activity.packageName}.fileprovider"
+    /**
+     * Open the native sharing interface to share a link, with its title and thumbnail in the preview
+     */
+    @Command
+    fun shareUrl(invoke: Invoke) {
+        val args = invoke.parseArgs(ShareUrlOptions::class.java)
 
-        // Create a content URI for the file
-        val contentUri = FileProvider.getUriForFile(activity, authority, tempFile)
+        val sendIntent = Intent().apply {
+            this.action = Intent.ACTION_SEND
+            this.type = "text/plain"
+            this.putExtra(Intent.EXTRA_TEXT, args.url)
+            this.putExtra(Intent.EXTRA_TITLE, args.title)
+        }
+
+        // Android 10+ shows the ClipData image as the preview thumbnail.
+        args.thumbnail?.let {
+            sendIntent.clipData = ClipData.newUri(activity.contentResolver, args.title ?: "", contentUri(it))
+            sendIntent.addFlags(Intent.FLAG_GRANT_READ_URI_PERMISSION)
+        }
+
+        val shareIntent = Intent.createChooser(sendIntent, null)
+        startActivityForResult(invoke, shareIntent, "shareUrlResult")
+    }
+
+    @ActivityCallback
+    private fun shareUrlResult(invoke: Invoke, result: ActivityResult) {
+        if (result.resultCode == Activity.RESULT_CANCELED) {
+            invoke.reject("Share cancelled", "CANCELLED")
+            return
+        }
+        invoke.resolve()
+    }
+
+    /**
+     * Open the native sharing interface to share a file
+     */
+    @Command
+    fun shareFile(invoke: Invoke) {
+        val args = invoke.parseArgs(ShareFileOptions::class.java)
+        
+        val contentUri = contentUri(args.url)
 
         val sendIntent = Intent().apply {
             this.action = Intent.ACTION_SEND
@@ -110,9 +195,140 @@ class SharePlugin(private val activity: Activity): Plugin(activity) {
     @ActivityCallback
     private fun shareFileResult(invoke: Invoke, result: ActivityResult) {
         if (result.resultCode == Activity.RESULT_CANCELED) {
-            invoke.reject("Share cancelled")
+            invoke.reject("Share cancelled", "CANCELLED")
             return
         }
         invoke.resolve()
     }
+
+    /**
+     * Open the native sharing interface to share several files at once
+     */
+    @Command
+    fun shareFiles(invoke: Invoke) {
+        val args = invoke.parseArgs(ShareFilesOptions::class.java)
+
+        val contentUris = ArrayList(args.files.map { contentUri(it.url) })
+
+        val sendIntent = Intent().apply {
+            this.action = Intent.ACTION_SEND_MULTIPLE
+            this.type = commonMimeType(args.files.map { it.mimeType })
+            this.putParcelableArrayListExtra(Intent.EXTRA_STREAM, contentUris)
+            this.addFlags(Intent.FLAG_GRANT_READ_URI_PERMISSION)
+            this.putExtra(Intent.EXTRA_TITLE, args.title)
+        }
+
+        if (contentUris.isNotEmpty()) {
+            val clipData = ClipData.newUri(activity.contentResolver, args.title ?: "", contentUris[0])
+            contentUris.drop(1).forEach { clipData.addItem(ClipData.Item(it)) }
+            sendIntent.clipData = clipData
+        }
+
+        val shareIntent = Intent.createChooser(sendIntent, args.title)
+        startActivityForResult(invoke, shareIntent, "shareFilesResult")
+    }
+
+    @ActivityCallback
+    private fun shareFilesResult(invoke: Invoke, result: ActivityResult) {
+        if (result.resultCode == Activity.RESULT_CANCELED) {
+            invoke.reject("Share cancelled", "CANCELLED")
+            return
+        }
+        invoke.resolve()
+    }
+
+    /**
+     * Open the native sharing interface to share a mix of text, links, HTML and files
+     */
+    @Command
+    fun share(invoke: Invoke) {
+        val args = invoke.parseArgs(ShareOptions::class.java)
+
+        val texts = mutableListOf<String>()
+        val html = StringBuilder()
+        val files = mutableListOf<ShareItem>()
+        for (item in args.items) {
+            when (item.type) {
+                "text" -> item.text?.let { texts.add(it) }
+                "url" -> item.url?.let { texts.add(it) }
+                "html" -> item.html?.let { html.append(it) }
+                "file" -> if (item.url != null) files.add(item)
+            }
+        }
+        // EXTRA_HTML_TEXT must be accompanied by a plain-text EXTRA_TEXT
+        if (html.isNotEmpty() && texts.isEmpty()) {
+            texts.add(HtmlCompat.fromHtml(html.toString(), HtmlCompat.FROM_HTML_MODE_LEGACY).toString())
+        }
+
+        val contentUris = ArrayList(files.map { contentUri(it.url!!) })
+
+        val sendIntent = Intent().apply {
+            this.action = if (contentUris.size > 1) Intent.ACTION_SEND_MULTIPLE else Intent.ACTION_SEND
+            this.type = when {
+                contentUris.isNotEmpty() -> commonMimeType(files.map { it.mimeType })
+                html.isNotEmpty() -> "text/html"
+                else -> "text/plain"
+            }
+            if (texts.isNotEmpty()) {
+                this.putExtra(Intent.EXTRA_TEXT, texts.joinToString("\n"))
+            }
+            if (html.isNotEmpty()) {
+                this.putExtra(Intent.EXTRA_HTML_TEXT, html.toString())
+            }
+            when (contentUris.size) {
+                0 -> {}
+                1 -> this.putExtra(Intent.EXTRA_STREAM, contentUris[0])
+                else -> this.putParcelableArrayListExtra(Intent.EXTRA_STREAM, contentUris)
+            }
+            if (contentUris.isNotEmpty()) {
+                this.addFlags(Intent.FLAG_GRANT_READ_URI_PERMISSION)
+            }
+            this.putExtra(Intent.EXTRA_TITLE, args.title)
+        }
+
+        if (contentUris.isNotEmpty()) {
+            val clipData = ClipData.newUri(activity.contentResolver, args.title ?: "", contentUris[0])
+            contentUris.drop(1).forEach { clipData.addItem(ClipData.Item(it)) }
+            sendIntent.clipData = clipData
+        }
+
+        val shareIntent = Intent.createChooser(sendIntent, args.title)
+        startActivityForResult(invoke, shareIntent, "shareResult")
+    }
+
+    @ActivityCallback
+    private fun shareResult(invoke: Invoke, result: ActivityResult) {
+        if (result.resultCode == Activity.RESULT_CANCELED) {
+            invoke.reject("Share cancelled", "CANCELLED")
+            return
+        }
+        invoke.resolve()
+    }
+
+    /**
+     * Content URI other apps can read through the FileProvider for the file at [url].
+     * The Rust side has already copied the file into the plugin's staging directory
+     * below the cache directory, so it is shared as is.
+     */
+    private fun contentUri(url: String): Uri {
+        val file = if (url.startsWith("file://")) {
+            File(Uri.parse(url).path!!)
+        } else {
+            File(url)
+        }
+        val authority = "${activity.packageName}.fileprovider"
+        return FileProvider.getUriForFile(activity, authority, file)
+    }
+
+    /**
+     * Narrowest MIME type covering all [mimeTypes], keeping the shared top-level type if there is one
+     */
+    private fun commonMimeType(mimeTypes: List<String?>): String {
+        val known = mimeTypes.map { it ?: "*/*" }.distinct()
+        if (known.size == 1) {
+            return known[0]
+        }
+        val topLevel = known.map { it.substringBefore('/') }.distinct()
+        return if (topLevel.size == 1 && topLevel[0] != "*") "${topLevel[0]}/*" else "*/*"
+    }
 }
@@ -1,7 +1,38 @@
-const COMMANDS: &[&str] = &["share_text", "share_file"];
+use std::path::PathBuf;
+
+const COMMANDS: &[&str] = &[
+    "share_text",
+    "share_html",
+    "share_url",
+    "share_file",
+    "share_files",
+    "share_email",
+    "share",
+    "share_data",
+    "share_image",
+    "share_web",
+    "get_capabilities",
+    "can_share",
+];
+
+/// Entry of the `sharekit` scope, used to generate the capability schema.
+#[allow(dead_code)]
+#[derive(schemars::JsonSchema)]
+#[serde(untagged)]
+enum ScopeEntry {
+    /// A path that can be shared by the webview. Supports glob patterns and
+    /// base directory variables, e.g. `$DOCUMENT/**`.
+    Value(PathBuf),
+    Object {
+        /// A path that can be shared by the webview. Supports glob patterns
+        /// and base directory variables, e.g. `$DOCUMENT/**`.
+        path: PathBuf,
+    },
+}
 
 fn main() {
     tauri_plugin::Builder::new(COMMANDS)
+        .global_scope_schema(schemars::schema_for!(ScopeEntry))
         .android_path("android")
         .ios_path("ios")
         .build();
@@ -1,4 +1,43 @@
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
+
+/** Stable identifier of the reason a share failed. */
+export type ShareErrorCode =
+  | "CANCELLED"
+  | "UNSUPPORTED_PLATFORM"
+  | "WINDOW_NOT_FOUND"
+  | "PATH_NOT_ALLOWED"
+  | "INVALID_INPUT"
+  | "IO"
+  | "PLATFORM";
+
+/** Error value every share function rejects with. */
+export interface ShareError {
+  code: ShareErrorCode;
+  message: string;
+}
+
+/**
+ * Checks whether a rejection value is a {@link ShareError}.
+ *
+ * ```javascript
+ * import { shareText, isShareError } from "@choochmeque/tauri-plugin-sharekit-api";
+ * try {
+ *   await shareText('Hello!');
+ * } catch (e) {
+ *   if (isShareError(e) && e.code === 'CANCELLED') {
+ *     // the user dismissed the share sheet
+ *   }
+ * }
+ * ```
+ */
+export function isShareError(error: unknown): error is ShareError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    typeof (error as ShareError).code === "string" &&
+    typeof (error as ShareError).message === "string"
+  );
+}
 
 export interface SharePosition {
   x: number;
@@ -10,15 +49,178 @@ export interface SharePosition {
 export interface ShareTextOptions {
   /** Android only */
   mimeType?: string;
+  /** Title shown in the share sheet's preview */
+  title?: string;
+  /** Subject line when the text is sent by email */
+  subject?: string;
+  /** Position for the share sheet (iPad/macOS only) */
+  position?: SharePosition;
+}
+
+export interface ShareHtmlOptions {
+  /** Plain-text alternative; generated from the HTML when omitted */
+  text?: string;
+  /** Title shown in the share sheet's preview */
+  title?: string;
+  /** Subject line when the text is sent by email */
+  subject?: string;
+  /** Position for the share sheet (iPad/macOS only) */
+  position?: SharePosition;
+}
+
+export interface ShareUrlOptions {
+  /** Title of the linked page, shown in link previews */
+  title?: string;
+  /** Summary of the linked page (Windows preview, Linux email body) */
+  description?: string;
+  /** Path or `file://` URL of an image shown in link previews */
+  thumbnail?: string;
   /** Position for the share sheet (iPad/macOS only) */
   position?: SharePosition;
 }
 
+export interface ArchiveOptions {
+  /** Defaults to `zip` */
+  format?: "zip" | "tarGz";
+  /** Archive file name; the format's extension is added if missing */
+  name?: string;
+  /** Whether symbolic links inside directories are archived; defaults to `skip` */
+  symlinks?: "skip" | "follow";
+}
+
+/** How far writing an archive has come. */
+export interface ArchiveProgress {
+  filesDone: number;
+  filesTotal: number;
+  bytesDone: number;
+  bytesTotal: number;
+}
+
+export interface ImageOptions {
+  /** Largest width or height in pixels; larger images are scaled down and SVG images are rasterised to it */
+  maxDimension?: number;
+  /** JPEG and WebP quality from 1 to 100, 85 by default */
+  quality?: number;
+  /** Format to convert to; defaults to the image's own format, and `png` for SVG and GIF */
+  format?: "jpeg" | "png" | "webp";
+}
+
 export interface ShareFileOptions {
   mimeType?: string;
+  title?: string;
+  /** Name recipients see instead of the file's own name, e.g. `Invoice-2026-10.pdf` */
+  fileName?: string;
+  /** Position for the share sheet (iPad/macOS only) */
+  position?: SharePosition;
+  /** Shares the file packed into an archive; directories are always archived */
+  archive?: ArchiveOptions;
+  /** Called while an archive is written */
+  onProgress?: (progress: ArchiveProgress) => void;
+  /** Shares a resized or converted copy of an image */
+  image?: ImageOptions;
+  /** Shares a copy without privacy metadata such as locations and authors; defaults to the `stripMetadata` setting */
+  stripMetadata?: boolean;
+  /** Name of an encryption registered in Rust with `Builder::encryption`; only the encrypted file is shared */
+  encryption?: string;
+}
+
+export interface SharedFile {
+  /** The file URL to share */
+  url: string;
+  mimeType?: string;
+}
+
+export interface ShareEmailOptions {
+  /** Recipients, as `someone@example.com` or `Name <someone@example.com>` */
+  to?: string[];
+  cc?: string[];
+  subject?: string;
+  body?: string;
+}
+
+export interface ShareFilesOptions {
   title?: string;
   /** Position for the share sheet (iPad/macOS only) */
   position?: SharePosition;
+  /** Shares the files packed into one archive; groups with a directory are always archived */
+  archive?: ArchiveOptions;
+  /** Called while an archive is written */
+  onProgress?: (progress: ArchiveProgress) => void;
+  /** Shares copies without privacy metadata; defaults to the `stripMetadata` setting */
+  stripMetadata?: boolean;
+}
+
+export interface ShareDataOptions {
+  /** File name the receiving app sees, e.g. `export.png` */
+  name: string;
+  /** Defaults to the type of a `Blob` or the media type of a data URL */
+  mimeType?: string;
+  title?: string;
+  /** Position for the share sheet (iPad/macOS only) */
+  position?: SharePosition;
+  /** Shares a resized or converted copy of an image */
+  image?: ImageOptions;
+  /** Shares the content without privacy metadata; defaults to the `stripMetadata` setting */
+  stripMetadata?: boolean;
+  /** Name of an encryption registered in Rust with `Builder::encryption`; only the encrypted file is shared */
+  encryption?: string;
+}
+
+export interface ShareImageOptions {
+  /** File name the receiving app sees, `Image` by default; the format's extension is added */
+  name?: string;
+  /** `png` by default */
+  format?: "jpeg" | "png" | "webp";
+  /** JPEG and WebP quality from 1 to 100, 85 by default */
+  quality?: number;
+  title?: string;
+  /** Position for the share sheet (iPad/macOS only) */
+  position?: SharePosition;
+}
+
+/** Raw RGBA pixels, four bytes per pixel, row by row. */
+export interface RgbaImage {
+  rgba: Uint8Array | Uint8ClampedArray | ArrayBuffer;
+  width: number;
+  height: number;
+}
+
+/** A single piece of content in a mixed-content share. */
+export type ShareItem =
+  | { type: "text"; text: string }
+  | { type: "url"; url: string }
+  | { type: "file"; url: string; mimeType?: string }
+  | { type: "html"; html: string }
+  | {
+      type: "bytes";
+      data: Uint8Array | number[];
+      name: string;
+      mimeType?: string;
+    };
+
+/** What the platform's share backend supports. */
+export interface ShareCapabilities {
+  /** Text and HTML */
+  text: boolean;
+  /** Files and in-memory content */
+  files: boolean;
+  /** Several files in one share */
+  multipleFiles: boolean;
+  urls: boolean;
+  /** Shares only succeed once the user picked where the content goes */
+  reportsResult: boolean;
+  /** The share UI is anchored at `position` */
+  anchorsPosition: boolean;
+  /** Dismissing the share UI rejects with `CANCELLED` */
+  reportsDismissal: boolean;
+}
+
+export interface ShareOptions {
+  title?: string;
+  /** Position for the share sheet (iPad/macOS only) */
+  position?: SharePosition;
+  /** Shares files and bytes without privacy metadata; defaults to the `stripMetadata` setting */
+  stripMetadata?: boolean;
 }
 
 /**
@@ -42,6 +244,55 @@ export async function shareText(
   });
 }
 
+/**
+ * Opens the native sharing interface to share formatted text. The HTML is
+ * sanitized first, and targets that cannot show HTML get the plain text.
+ *
+ * ```javascript
+ * import { shareHtml } from "@choochmeque/tauri-plugin-sharekit-api";
+ * await shareHtml('<h1>Notes</h1><p>Buy <b>milk</b></p>', { subject: 'Notes' });
+ * ```
+ * @param html - The formatted text to share
+ * @param options - Plain-text alternative, title and subject
+ * @returns
+ */
+export async function shareHtml(
+  html: string,
+  options?: ShareHtmlOptions,
+): Promise<void> {
+  await invoke("plugin:sharekit|share_html", {
+    html,
+    ...options,
+  });
+}
+
+/**
+ * Opens the native sharing interface to share a link. Share targets get
+ * a real URL rather than text, so they can show a link preview. The URL is
+ * normalized first; local URLs such as `blob:` and `file:` are rejected.
+ *
+ * ```javascript
+ * import { shareUrl } from "@choochmeque/tauri-plugin-sharekit-api";
+ * await shareUrl('https://tauri.app', {
+ *   title: 'Tauri',
+ *   description: 'Build smaller, faster and more secure apps',
+ *   thumbnail: '/path/to/preview.png',
+ * });
+ * ```
+ * @param url - The link to share
+ * @param options - Title, description and thumbnail of the linked page
+ * @returns
+ */
+export async function shareUrl(
+  url: string,
+  options?: ShareUrlOptions,
+): Promise<void> {
+  await invoke("plugin:sharekit|share_url", {
+    url,
+    ...options,
+  });
+}
+
 /**
  * Opens the native sharing interface to share a file.
  *
@@ -52,16 +303,231 @@ export async function shareText(
  *   title: 'Document.pdf'
  * });
  * ```
- * @param url - The file URL to share (must be a file:// URL)
- * @param options - Optional settings including MIME type and title
+ * @param url - The file URL to share (must be a file:// URL); directories are shared as an archive
+ * @param options - Optional settings including MIME type, title and archive options
  * @returns
  */
 export async function shareFile(
   url: string,
   options?: ShareFileOptions,
 ): Promise<void> {
+  const { onProgress, ...rest } = options ?? {};
   await invoke("plugin:sharekit|share_file", {
     url,
+    ...rest,
+    onProgress: progressChannel(onProgress),
+  });
+}
+
+/**
+ * Opens the native sharing interface to share several files at once.
+ *
+ * ```javascript
+ * import { shareFiles } from "@choochmeque/tauri-plugin-sharekit-api";
+ * await shareFiles([
+ *   { url: 'file:///path/to/photo1.jpg', mimeType: 'image/jpeg' },
+ *   { url: 'file:///path/to/photo2.jpg', mimeType: 'image/jpeg' },
+ * ], { title: 'Holiday photos' });
+ * ```
+ * @param files - The files to share, each with an optional MIME type
+ * @param options - Optional settings including title
+ * @returns
+ */
+export async function shareFiles(
+  files: SharedFile[],
+  options?: ShareFilesOptions,
+): Promise<void> {
+  const { onProgress, ...rest } = options ?? {};
+  await invoke("plugin:sharekit|share_files", {
+    files,
+    ...rest,
+    onProgress: progressChannel(onProgress),
+  });
+}
+
+/**
+ * Opens a new email with the files attached. Windows and macOS open a
+ * draft `.eml` message in the default mail client, Linux composes the email
+ * through the desktop portal or `xdg-email`, and mobile platforms open the
+ * share sheet with the body and files.
+ *
+ * ```javascript
+ * import { shareEmail } from "@choochmeque/tauri-plugin-sharekit-api";
+ * await shareEmail([{ url: 'file:///path/to/report.pdf' }], {
+ *   to: ['team@example.com'],
+ *   subject: 'Monthly report',
+ *   body: 'Report attached.',
+ * });
+ * ```
+ * @param files - The files to attach, each with an optional MIME type
+ * @param options - Recipients, subject and body
+ * @returns
+ */
+export async function shareEmail(
+  files: SharedFile[],
+  options?: ShareEmailOptions,
+): Promise<void> {
+  await invoke("plugin:sharekit|share_email", {
+    files,
+    ...options,
+  });
+}
+
+function progressChannel(
+  onProgress?: (progress: ArchiveProgress) => void,
+): Channel<ArchiveProgress> | undefined {
+  if (!onProgress) {
+    return undefined;
+  }
+  const channel = new Channel<ArchiveProgress>();
+  channel.onmessage = onProgress;
+  return channel;
+}
+
+/**
+ * Opens the native sharing interface to share a mix of text, links, HTML
+ * and files in a single request.
+ *
+ * ```javascript
+ * import { share } from "@choochmeque/tauri-plugin-sharekit-api";
+ * await share([
+ *   { type: 'text', text: 'Our trip report' },
+ *   { type: 'url', url: 'https://example.com/trip' },
+ *   { type: 'file', url: 'file:///path/to/report.pdf', mimeType: 'application/pdf' },
+ * ], { title: 'Trip report' });
+ * ```
+ * @param items - The items to share
+ * @param options - Optional settings including title
+ * @returns
+ */
+export async function share(
+  items: ShareItem[],
+  options?: ShareOptions,
+): Promise<void> {
+  await invoke("plugin:sharekit|share", {
+    items: itemsToJson(items),
     ...options,
   });
 }
+
+function itemsToJson(items: ShareItem[]) {
+  return items.map((item) =>
+    item.type === "bytes" ? { ...item, data: Array.from(item.data) } : item,
+  );
+}
+
+/**
+ * Reports what the platform's share backend supports, e.g. to hide a share
+ * button where sharing does not work.
+ *
+ * ```javascript
+ * import { getCapabilities } from "@choochmeque/tauri-plugin-sharekit-api";
+ * const { files } = await getCapabilities();
+ * exportButton.hidden = !files;
+ * ```
+ * @returns
+ */
+export async function getCapabilities(): Promise<ShareCapabilities> {
+  return await invoke("plugin:sharekit|get_capabilities");
+}
+
+/**
+ * Checks whether {@link share} can share `items`, without showing any UI:
+ * the backend supports them and the files among them are in scope.
+ *
+ * ```javascript
+ * import { canShare } from "@choochmeque/tauri-plugin-sharekit-api";
+ * const items = [{ type: 'file', url: 'file:///path/to/a.pdf' }, { type: 'file', url: 'file:///path/to/b.pdf' }];
+ * shareButton.hidden = !(await canShare(items));
+ * ```
+ * @param items - The items that would be shared
+ * @returns
+ */
+export async function canShare(items: ShareItem[]): Promise<boolean> {
+  return await invoke("plugin:sharekit|can_share", {
+    items: itemsToJson(items),
+  });
+}
+
+/**
+ * Opens the native sharing interface to share in-memory content as a file.
+ * The plugin writes the content to a temporary file, so no filesystem
+ * access is needed in the webview.
+ *
+ * Binary content is sent as a raw IPC body instead of JSON, so large blobs
+ * are not base64-encoded on the way.
+ *
+ * ```javascript
+ * import { shareData } from "@choochmeque/tauri-plugin-sharekit-api";
+ * const pdf = await generatePdf(); // Uint8Array
+ * await shareData(pdf, { name: 'invoice.pdf', mimeType: 'application/pdf' });
+ * await shareData('data:image/png;base64,iVBORw0KGgo...', { name: 'chart.png' });
+ * ```
+ * @param data - Bytes, a `Blob`, a base64 string or a `data:` URL
+ * @param options - File name and optional MIME type and title
+ * @returns
+ */
+export async function shareData(
+  data: Uint8Array | ArrayBuffer | Blob | string,
+  options: ShareDataOptions,
+): Promise<void> {
+  if (typeof data === "string") {
+    await invoke("plugin:sharekit|share_data", { data, ...options });
+    return;
+  }
+
+  if (data instanceof Blob) {
+    options = {
+      ...options,
+      mimeType: options.mimeType ?? (data.type || undefined),
+    };
+    data = await data.arrayBuffer();
+  }
+  await invoke("plugin:sharekit|share_data", data, {
+    headers: {
+      "x-sharekit-options": encodeURIComponent(JSON.stringify(options)),
+    },
+  });
+}
+
+/**
+ * Opens the native sharing interface to share an image, encoded by the
+ * plugin as PNG, JPEG or WebP. Pass raw RGBA pixels, such as canvas
+ * `ImageData`, or an encoded JPEG, PNG, WebP or GIF image.
+ *
+ * ```javascript
+ * import { shareImage } from "@choochmeque/tauri-plugin-sharekit-api";
+ * const pixels = canvas.getContext('2d').getImageData(0, 0, canvas.width, canvas.height);
+ * await shareImage({ rgba: pixels.data, width: pixels.width, height: pixels.height }, { name: 'Chart' });
+ * await shareImage(await fetch(url).then((r) => r.blob()), { format: 'jpeg', quality: 80 });
+ * ```
+ * @param image - RGBA pixels with their size, or encoded image bytes
+ * @param options - File name, format, quality and title
+ * @returns
+ */
+export async function shareImage(
+  image: RgbaImage | ImageData | Uint8Array | ArrayBuffer | Blob,
+  options: ShareImageOptions = {},
+): Promise<void> {
+  let data: Uint8Array | ArrayBuffer;
+  let size: { width?: number; height?: number } = {};
+  if (image instanceof Blob) {
+    data = await image.arrayBuffer();
+  } else if (image instanceof Uint8Array || image instanceof ArrayBuffer) {
+    data = image;
+  } else {
+    const rgba = "rgba" in image ? image.rgba : image.data;
+    data =
+      rgba instanceof ArrayBuffer
+        ? rgba
+        : new Uint8Array(rgba.buffer, rgba.byteOffset, rgba.byteLength);
+    size = { width: image.width, height: image.height };
+  }
+  await invoke("plugin:sharekit|share_image", data, {
+    headers: {
+      "x-sharekit-options": encodeURIComponent(
+        JSON.stringify({ ...options, ...size }),
+      ),
+    },
+  });
+}
@@ -1,3 +1,4 @@
+import LinkPresentation
 import LocalAuthentication
 import SwiftRs
 import Tauri
@@ -15,6 +16,23 @@ struct SharePosition: Decodable {
 
 struct ShareOptions: Decodable {
   let text: String
+  let title: String?
+  let subject: String?
+  let position: SharePosition?
+}
+
+struct ShareHtmlOptions: Decodable {
+  let html: String
+  let text: String
+  let title: String?
+  let subject: String?
+  let position: SharePosition?
+}
+
+struct ShareUrlOptions: Decodable {
+  let url: String
+  let title: String?
+  let thumbnail: String?
   let position: SharePosition?
 }
 
@@ -24,6 +42,121 @@ struct ShareFileOptions: Decodable {
   let position: SharePosition?
 }
 
+struct SharedFile: Decodable {
+  let url: String
+  let mimeType: String?
+}
+
+struct ShareFilesOptions: Decodable {
+  let files: [SharedFile]
+  let title: String?
+  let position: SharePosition?
+}
+
+struct ShareItem: Decodable {
+  let type: String
+  let text: String?
+  let url: String?
+  let mimeType: String?
+  let html: String?
+}
+
+struct ShareItemsOptions: Decodable {
+  let items: [ShareItem]
+  let title: String?
+  let position: SharePosition?
+}
+
+/// Shares a link with the title and thumbnail the share sheet shows in its preview.
+class LinkItemSource: NSObject, UIActivityItemSource {
+  let url: URL
+  let metadata = LPLinkMetadata()
+
+  init(url: URL, title: String?, thumbnail: URL?) {
+    self.url = url
+    metadata.originalURL = url
+    metadata.url = url
+    metadata.title = title
+    if let thumbnail = thumbnail {
+      metadata.imageProvider = NSItemProvider(contentsOf: thumbnail)
+    }
+  }
+
+  func activityViewControllerPlaceholderItem(_ activityViewController: UIActivityViewController) -> Any {
+    return url
+  }
+
+  func activityViewController(
+    _ activityViewController: UIActivityViewController,
+    itemForActivityType activityType: UIActivity.ActivityType?
+  ) -> Any? {
+    return url
+  }
+
+  func activityViewControllerLinkMetadata(_ activityViewController: UIActivityViewController) -> LPLinkMetadata? {
+    return metadata
+  }
+}
+
+/// Shares text with the title the share sheet shows in its preview and the subject mail uses.
+class TextItemSource: NSObject, UIActivityItemSource {
+  let text: String
+  let subject: String?
+  let metadata = LPLinkMetadata()
+
+  init(text: String, title: String?, subject: String?) {
+    self.text = text
+    self.subject = subject
+    metadata.title = title ?? subject
+  }
+
+  func activityViewControllerPlaceholderItem(_ activityViewController: UIActivityViewController) -> Any {
+    return text
+  }
+
+  func activityViewController(
+    _ activityViewController: UIActivityViewController,
+    itemForActivityType activityType: UIActivity.ActivityType?
+  ) -> Any? {
+    return text
+  }
+
+  func activityViewController(
+    _ activityViewController: UIActivityViewController,
+    subjectForActivityType activityType: UIActivity.ActivityType?
+  ) -> String {
+    return subject ?? ""
+  }
+
+  func activityViewControllerLinkMetadata(_ activityViewController: UIActivityViewController) -> LPLinkMetadata? {
+    return metadata.title == nil ? nil : metadata
+  }
+}
+
+/// Shares HTML as an attributed string, or as its plain text where it cannot be read.
+class HtmlItemSource: TextItemSource {
+  let attributed: NSAttributedString?
+
+  init(html: String, text: String, title: String?, subject: String?) {
+    attributed = try? NSAttributedString(
+      data: Data(html.utf8),
+      options: [
+        .documentType: NSAttributedString.DocumentType.html,
+        .characterEncoding: String.Encoding.utf8.rawValue,
+      ],
+      documentAttributes: nil
+    )
+    super.init(text: text, title: title, subject: subject)
+  }
+
+  override func activityViewController(
+    _ activityViewController: UIActivityViewController,
+    itemForActivityType activityType: UIActivity.ActivityType?
+  ) -> Any? {
+    return attributed ?? text
+  }
+}
+
 class SharePlugin: Plugin {
   var webview: WKWebView!
   public override func load(webview: WKWebView) {
@@ -34,7 +167,41 @@ class SharePlugin: Plugin {
     let args = try invoke.parseArgs(ShareOptions.self)
 
     DispatchQueue.main.async {
-      let activityViewController = UIActivityViewController(activityItems: [args.text], applicationActivities: nil)
+      let source = TextItemSource(text: args.text, title: args.title, subject: args.subject)
+      let activityViewController = UIActivityViewController(activityItems: [source], applicationActivities: nil)
+
+      // Display as popover on iPad as required by Apple
+      let posX = args.position?.x ?? Double(self.webview.bounds.midX)
+      let posY = args.position?.y ?? Double(self.webview.bounds.midY)
+      activityViewController.popoverPresentationController?.sourceView = self.webview
+      activityViewController.popoverPresentationController?.sourceRect = CGRect(
+        x: posX,
+        y: posY,
+        width: 0.0,
+        height: 0.0
+      )
+
+      activityViewController.completionWithItemsHandler = { _, completed, _, error in
+        if let error = error {
+          invoke.reject(error.localizedDescription)
+        } else if completed {
+          invoke.resolve()
+        } else {
+          invoke.reject("Share cancelled", code: "CANCELLED")
+        }
+      }
+
+      self.manager.viewController?.present(activityViewController, animated: true, completion: nil)
+    }
+  }
+
+  @objc func shareHtml(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(ShareHtmlOptions.self)
+
+    DispatchQueue.main.async {
+      // Reading HTML into an attributed string has to happen on the main thread.
+      let source = HtmlItemSource(html: args.html, text: args.text, title: args.title, subject: args.subject)
+      let activityViewController = UIActivityViewController(activityItems: [source], applicationActivities: nil)
 
       // Display as popover on iPad as required by Apple
       let posX = args.position?.x ?? Double(self.webview.bounds.midX)
@@ -53,7 +220,49 @@ class SharePlugin: Plugin {
         } else if completed {
           invoke.resolve()
         } else {
-          invoke.reject("Share cancelled")
+          invoke.reject("Share cancelled", code: "CANCELLED")
+        }
+      }
+
+      self.manager.viewController?.present(activityViewController, animated: true, completion: nil)
+    }
+  }
+
+  @objc func shareUrl(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(ShareUrlOptions.self)
+
+    DispatchQueue.main.async {
+      guard let url = URL(string: args.url) else {
+        invoke.reject("Invalid URL")
+        return
+      }
+      // The Rust side has already copied the thumbnail into the staging directory.
+      let thumbnail = args.thumbnail.flatMap { URL(string: $0) }
+      let source = LinkItemSource(url: url, title: args.title, thumbnail: thumbnail)
+
+      let activityViewController = UIActivityViewController(
+        activityItems: [source],
+        applicationActivities: nil
+      )
+
+      // Display as popover on iPad as required by Apple
+      let posX = args.position?.x ?? Double(self.webview.bounds.midX)
+      let posY = args.position?.y ?? Double(self.webview.bounds.midY)
+      activityViewController.popoverPresentationController?.sourceView = self.webview
+      activityViewController.popoverPresentationController?.sourceRect = CGRect(
+        x: posX,
+        y: posY,
+        width: 0.0,
+        height: 0.0
+      )
+
+      activityViewController.completionWithItemsHandler = { _, completed, _, error in
+        if let error = error {
+          invoke.reject(error.localizedDescription)
+        } else if completed {
+          invoke.resolve()
+        } else {
+          invoke.reject("Share cancelled", code: "CANCELLED")
         }
       }
 
@@ -71,14 +280,9 @@ class SharePlugin: Plugin {
         return
       }
 
-      let fileManager = FileManager.default
-      let tempDirectory = fileManager.temporaryDirectory
-      let tempPath = tempDirectory.path + "/" + fileUrl.lastPathComponent
-      let tempURL = URL(fileURLWithPath: tempPath)
-      try? fileManager.copyItem(atPath:fileUrl.path , toPath: tempPath)
+      // The Rust side has already copied the file into the staging directory.
+      let activityItems: [Any] = [fileUrl]
 
-      var activityItems: [Any] = [tempURL]
-      
       let activityViewController = UIActivityViewController(
         activityItems: activityItems,
         applicationActivities: nil
@@ -101,7 +305,118 @@ class SharePlugin: Plugin {
         } else if completed {
           invoke.resolve()
         } else {
-          invoke.reject("Share cancelled")
+          invoke.reject("Share cancelled", code: "CANCELLED")
+        }
+      }
+
+      self.manager.viewController?.present(activityViewController, animated: true, completion: nil)
+    }
+  }
+
+  @objc func shareFiles(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(ShareFilesOptions.self)
+
+    DispatchQueue.main.async {
+      var activityItems: [Any] = []
+      for file in args.files {
+        guard let fileUrl = URL(string: file.url) else {
+          invoke.reject("Invalid file URL")
+          return
+        }
+        activityItems.append(fileUrl)
+      }
+
+      let activityViewController = UIActivityViewController(
+        activityItems: activityItems,
+        applicationActivities: nil
+      )
+
+      // Display as popover on iPad as required by Apple
+      let posX = args.position?.x ?? Double(self.webview.bounds.midX)
+      let posY = args.position?.y ?? Double(self.webview.bounds.midY)
+      activityViewController.popoverPresentationController?.sourceView = self.webview
+      activityViewController.popoverPresentationController?.sourceRect = CGRect(
+        x: posX,
+        y: posY,
+        width: 0.0,
+        height: 0.0
+      )
+
+      activityViewController.completionWithItemsHandler = { _, completed, _, error in
+        if let error = error {
+          invoke.reject(error.localizedDescription)
+        } else if completed {
+          invoke.resolve()
+        } else {
+          invoke.reject("Share cancelled", code: "CANCELLED")
+        }
+      }
+
+      self.manager.viewController?.present(activityViewController, animated: true, completion: nil)
+    }
+  }
+
+  @objc func share(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(ShareItemsOptions.self)
+
+    DispatchQueue.main.async {
+      var activityItems: [Any] = []
+      for item in args.items {
+        switch item.type {
+        case "text":
+          if let text = item.text { activityItems.append(text) }
+        case "url":
+          if let url = item.url {
+            if let link = URL(string: url) {
+              activityItems.append(link)
+            } else {
+              activityItems.append(url)
+            }
+          }
+        case "html":
+          if let html = item.html, let data = html.data(using: .utf8),
+            let attributed = try? NSAttributedString(
+              data: data,
+              options: [.documentType: NSAttributedString.DocumentType.html],
+              documentAttributes: nil
+            )
+          {
+            activityItems.append(attributed)
+          }
+        case "file":
+          guard let url = item.url, let fileUrl = URL(string: url) else {
+            invoke.reject("Invalid file URL")
+            return
+          }
+          activityItems.append(fileUrl)
+        default:
+          break
+        }
+      }
+
+      let activityViewController = UIActivityViewController(
+        activityItems: activityItems,
+        applicationActivities: nil
+      )
+
+      // Display as popover on iPad as required by Apple
+      let posX = args.position?.x ?? Double(self.webview.bounds.midX)
+      let posY = args.position?.y ?? Double(self.webview.bounds.midY)
+      activityViewController.popoverPresentationController?.sourceView = self.webview
+      activityViewController.popoverPresentationController?.sourceRect = CGRect(
+        x: posX,
+        y: posY,
+        width: 0.0,
+        height: 0.0
+      )
+
+      activityViewController.completionWithItemsHandler = { _, completed, _, error in
+        if let error = error {
+          invoke.reject(error.localizedDescription)
+        } else if completed {
+          invoke.resolve()
+        } else {
+          invoke.reject("Share cancelled", code: "CANCELLED")
         }
       }
 
@@ -0,0 +1,558 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use time::{OffsetDateTime, PrimitiveDateTime};
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, DateTime, ZipWriter};
+
+use crate::metadata;
+use crate::models::{
+    ArchiveFormat, ArchiveOptions, ArchiveProgress, ProgressHandler, SymlinkPolicy,
+};
+
+/// Bytes written between two progress reports within a file.
+const REPORT_INTERVAL: u64 = 1024 * 1024;
+
+/// A file or directory to put into an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub source: PathBuf,
+    /// Path inside the archive, with `/` separators.
+    pub name: String,
+    pub kind: EntryKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File { len: u64 },
+    Dir,
+}
+
+/// Whether sharing `paths` produces an archive: when one was asked for, or
+/// when one of the paths is a directory.
+pub fn is_needed(paths: &[PathBuf], options: Option<&ArchiveOptions>) -> bool {
+    options.is_some() || paths.iter().any(|path| path.is_dir())
+}
+
+/// Everything below `sources` that goes into the archive, parents before
+/// their children. Each source is stored under its own file name at the
+/// top of the archive, numbered like `report (2).pdf` if names collide.
+///
+/// Anything that is neither a file nor a directory, such as a socket or a
+/// link to a missing target, is left out.
+pub fn list(sources: &[PathBuf], symlinks: SymlinkPolicy) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let mut names = HashSet::new();
+    for source in sources {
+        let name = source
+            .file_name()
+            .map_or_else(|| "file".into(), |name| name.to_string_lossy().into_owned());
+        let name = unique_name(&mut names, &name);
+        walk(source, name, symlinks, &mut Vec::new(), &mut entries)?;
+    }
+    Ok(entries)
+}
+
+fn unique_name(names: &mut HashSet<String>, name: &str) -> String {
+    if names.insert(name.to_owned()) {
+        return name.to_owned();
+    }
+    let (stem, extension) = crate::staging::split_extension(name);
+    let mut n = 2;
+    loop {
+        let numbered = format!("{stem} ({n}){extension}");
+        if names.insert(numbered.clone()) {
+            return numbered;
+        }
+        n += 1;
+    }
+}
+
+/// Adds `path` and, for directories, everything below it. `ancestors` holds
+/// the canonical paths of the directories being walked, so a followed link
+/// to one of them does not recurse forever.
+fn walk(
+    path: &Path,
+    name: String,
+    symlinks: SymlinkPolicy,
+    ancestors: &mut Vec<PathBuf>,
+    entries: &mut Vec<Entry>,
+) -> io::Result<()> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !ancestors.is_empty() => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if metadata.is_file() {
+        entries.push(Entry {
+            source: path.to_owned(),
+            name,
+            kind: EntryKind::File {
+                len: metadata.len(),
+            },
+        });
+        return Ok(());
+    }
+    if !metadata.is_dir() {
+        return Ok(());
+    }
+
+    let canonical = fs::canonicalize(path)?;
+    if ancestors.contains(&canonical) {
+        return Ok(());
+    }
+    entries.push(Entry {
+        source: path.to_owned(),
+        name: name.clone(),
+        kind: EntryKind::Dir,
+    });
+
+    let mut children = fs::read_dir(path)?.collect::<io::Result<Vec<_>>>()?;
+    children.sort_by_key(fs::DirEntry::file_name);
+    ancestors.push(canonical);
+    for child in children {
+        if symlinks == SymlinkPolicy::Skip && child.file_type()?.is_symlink() {
+            continue;
+        }
+        let child_name = format!("{name}/{}", child.file_name().to_string_lossy());
+        walk(&child.path(), child_name, symlinks, ancestors, entries)?;
+    }
+    ancestors.pop();
+    Ok(())
+}
+
+/// File name of the archive for `sources`: the configured name, or the name
+/// of a lone source, or `Archive`, ending in the format's extension.
+pub fn file_name(sources: &[PathBuf], options: &ArchiveOptions) -> String {
+    let extension = extension(options.format);
+    let name = match (&options.name, sources) {
+        (Some(name), _) => name.clone(),
+        (None, [source]) => source.file_name().map_or_else(
+            || "Archive".into(),
+            |name| name.to_string_lossy().into_owned(),
+        ),
+        (None, _) => "Archive".into(),
+    };
+    if name.to_lowercase().ends_with(extension) {
+        name
+    } else {
+        format!("{name}{extension}")
+    }
+}
+
+const fn extension(format: ArchiveFormat) -> &'static str {
+    match format {
+        ArchiveFormat::Zip => ".zip",
+        ArchiveFormat::TarGz => ".tar.gz",
+    }
+}
+
+/// Writes `entries` to `file` in the format of `options`, reporting
+/// progress to its handler. Zip archives encrypt every file with AES-256
+/// under `password`, if there is one. With `strip`, files go in without
+/// their privacy metadata.
+pub fn write(
+    file: File,
+    entries: &[Entry],
+    options: &ArchiveOptions,
+    password: Option<&str>,
+    strip: bool,
+) -> io::Result<()> {
+    write_with(file, ZipWriter::new, entries, options, password, strip)
+}
+
+/// Like [`write`], for outputs that cannot seek, such as an encrypting
+/// writer.
+pub fn write_stream(
+    out: impl Write,
+    entries: &[Entry],
+    options: &ArchiveOptions,
+    password: Option<&str>,
+    strip: bool,
+) -> io::Result<()> {
+    write_with(
+        out,
+        ZipWriter::new_stream,
+        entries,
+        options,
+        password,
+        strip,
+    )
+}
+
+fn write_with<W: Write, Z: Write + Seek>(
+    out: W,
+    zip_writer: impl FnOnce(W) -> ZipWriter<Z>,
+    entries: &[Entry],
+    options: &ArchiveOptions,
+    password: Option<&str>,
+    strip: bool,
+) -> io::Result<()> {
+    let mut progress = Progress::new(entries, options.progress.as_ref());
+    progress.report();
+    match options.format {
+        ArchiveFormat::Zip => write_zip(zip_writer(out), entries, &mut progress, password, strip),
+        ArchiveFormat::TarGz => write_tar_gz(out, entries, &mut progress, strip),
+    }
+}
+
+fn write_zip<W: Write + Seek>(
+    mut zip: ZipWriter<W>,
+    entries: &[Entry],
+    progress: &mut Progress<'_>,
+    password: Option<&str>,
+    strip: bool,
+) -> io::Result<()> {
+    for entry in entries {
+        let modified = fs::metadata(&entry.source)?.modified()?;
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(zip_time(modified));
+        match entry.kind {
+            EntryKind::Dir => zip.add_directory(entry.name.as_str(), options)?,
+            EntryKind::File { len } => {
+                let mut options = options.large_file(len > u64::from(u32::MAX));
+                if let Some(password) = password {
+                    options = options.with_aes_encryption(AesMode::Aes256, password);
+                }
+                zip.start_file(&entry.name, options)?;
+                let (content, _) = content(entry, strip)?;
+                io::copy(&mut progress.reader(content), &mut zip)?;
+                progress.file_done(len);
+            }
+        }
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+fn write_tar_gz(
+    out: impl Write,
+    entries: &[Entry],
+    progress: &mut Progress<'_>,
+    strip: bool,
+) -> io::Result<()> {
+    let mut tar = tar::Builder::new(GzEncoder::new(out, Compression::default()));
+    for entry in entries {
+        match entry.kind {
+            EntryKind::Dir => tar.append_dir(&entry.name, &entry.source)?,
+            EntryKind::File { len } => {
+                let mut header = tar::Header::new_gnu();
+                header.set_metadata(&fs::metadata(&entry.source)?);
+                let (content, content_len) = content(entry, strip)?;
+                header.set_size(content_len);
+                tar.append_data(&mut header, &entry.name, progress.reader(content))?;
+                progress.file_done(len);
+            }
+        }
+    }
+    tar.into_inner()?.finish()?.flush()
+}
+
+/// Content of a file entry, without privacy metadata with `strip`, and its
+/// size.
+fn content(entry: &Entry, strip: bool) -> io::Result<(Box<dyn Read>, u64)> {
+    if strip {
+        if let Some(data) = metadata::strip_file(&entry.source)? {
+            let len = data.len() as u64;
+            return Ok((Box::new(io::Cursor::new(data)), len));
+        }
+    }
+    let file = File::open(&entry.source)?;
+    let len = file.metadata()?.len();
+    Ok((Box::new(file), len))
+}
+
+/// Converts `time` to a zip timestamp in UTC. Times zip cannot represent,
+/// before 1980 or after 2107, become 1980-01-01.
+fn zip_time(time: SystemTime) -> DateTime {
+    let utc = time
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|since| i64::try_from(since.as_secs()).ok())
+        .and_then(|secs| OffsetDateTime::from_unix_timestamp(secs).ok());
+    utc.and_then(|utc| DateTime::try_from(PrimitiveDateTime::new(utc.date(), utc.time())).ok())
+        .unwrap_or(DateTime::DEFAULT)
+}
+
+/// Progress of writing one archive.
+struct Progress<'a> {
+    handler: Option<&'a ProgressHandler>,
+    state: ArchiveProgress,
+    reported_bytes: u64,
+    /// Size of the files done, as listed.
+    done_bytes: u64,
+}
+
+impl<'a> Progress<'a> {
+    fn new(entries: &[Entry], handler: Option<&'a ProgressHandler>) -> Self {
+        let mut state = ArchiveProgress {
+            files_done: 0,
+            files_total: 0,
+            bytes_done: 0,
+            bytes_total: 0,
+        };
+        for entry in entries {
+            if let EntryKind::File { len } = entry.kind {
+                state.files_total += 1;
+                state.bytes_total += len;
+            }
+        }
+        Self {
+            handler,
+            state,
+            reported_bytes: 0,
+            done_bytes: 0,
+        }
+    }
+
+    const fn reader<R: Read>(&mut self, inner: R) -> Counting<'_, 'a, R> {
+        Counting {
+            inner,
+            progress: self,
+        }
+    }
+
+    fn add_bytes(&mut self, n: u64) {
+        self.state.bytes_done += n;
+        if self.state.bytes_done - self.reported_bytes >= REPORT_INTERVAL {
+            self.report();
+        }
+    }
+
+    /// Counts a file of `len` bytes as done. Stripped files are read at
+    /// their cleaned size, so the bytes done are set to the listed sizes.
+    fn file_done(&mut self, len: u64) {
+        self.done_bytes += len;
+        self.state.bytes_done = self.done_bytes;
+        self.state.files_done += 1;
+        self.report();
+    }
+
+    fn report(&mut self) {
+        self.reported_bytes = self.state.bytes_done;
+        if let Some(handler) = self.handler {
+            handler.report(self.state);
+        }
+    }
+}
+
+/// Reader that counts the bytes read through it towards the progress.
+struct Counting<'p, 'a, R> {
+    inner: R,
+    progress: &'p mut Progress<'a>,
+}
+
+impl<R: Read> Read for Counting<'_, '_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.progress.add_bytes(n as u64);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn names(entries: &[Entry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    fn photos(dir: &TempDir) -> PathBuf {
+        dir.file("photos/b.jpg");
+        dir.file("photos/a.jpg");
+        dir.file("photos/2025/c.jpg");
+        dir.0.join("photos")
+    }
+
+    #[test]
+    fn directories_are_listed_parents_first() {
+        let dir = TempDir::new("archive");
+        let entries = list(&[photos(&dir)], SymlinkPolicy::Skip).expect("list");
+
+        assert_eq!(
+            names(&entries),
+            [
+                "photos",
+                "photos/2025",
+                "photos/2025/c.jpg",
+                "photos/a.jpg",
+                "photos/b.jpg"
+            ]
+        );
+        assert_eq!(entries[0].kind, EntryKind::Dir);
+        assert_eq!(entries[2].kind, EntryKind::File { len: 4 });
+    }
+
+    #[test]
+    fn colliding_top_level_names_are_numbered() {
+        let dir = TempDir::new("archive");
+        let sources = [dir.file("a/report.pdf"), dir.file("b/report.pdf")];
+
+        let entries = list(&sources, SymlinkPolicy::Skip).expect("list");
+        assert_eq!(names(&entries), ["report.pdf", "report (2).pdf"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_follow_the_policy() {
+        let dir = TempDir::new("archive");
+        let root = photos(&dir);
+        let outside = dir.file("outside/notes.txt");
+        std::os::unix::fs::symlink(&outside, root.join("notes.txt")).expect("symlink");
+        std::os::unix::fs::symlink(&root, root.join("2025/loop")).expect("symlink");
+        std::os::unix::fs::symlink(dir.0.join("missing"), root.join("dangling")).expect("symlink");
+
+        let skipped = list(std::slice::from_ref(&root), SymlinkPolicy::Skip).expect("list");
+        assert!(!names(&skipped).contains(&"photos/notes.txt"));
+
+        let followed = list(&[root], SymlinkPolicy::Follow).expect("list");
+        assert_eq!(
+            names(&followed),
+            [
+                "photos",
+                "photos/2025",
+                "photos/2025/c.jpg",
+                "photos/a.jpg",
+                "photos/b.jpg",
+                "photos/notes.txt",
+            ]
+        );
+        assert_eq!(followed[5].source, dir.0.join("photos/notes.txt"));
+    }
+
+    #[test]
+    fn archive_names_end_in_the_format_extension() {
+        let zip = ArchiveOptions::default();
+        let tar_gz = ArchiveOptions {
+            format: ArchiveFormat::TarGz,
+            ..ArchiveOptions::default()
+        };
+        let named = |name: &str| ArchiveOptions {
+            name: Some(name.into()),
+            ..ArchiveOptions::default()
+        };
+        let photos = [PathBuf::from("/tmp/photos")];
+        let files = [PathBuf::from("/tmp/a.txt"), PathBuf::from("/tmp/b.txt")];
+
+        assert_eq!(file_name(&photos, &zip), "photos.zip");
+        assert_eq!(file_name(&photos, &tar_gz), "photos.tar.gz");
+        assert_eq!(file_name(&files, &zip), "Archive.zip");
+        assert_eq!(file_name(&files, &named("Trip")), "Trip.zip");
+        assert_eq!(file_name(&files, &named("Trip.ZIP")), "Trip.ZIP");
+    }
+
+    fn write_archive(dir: &TempDir, format: ArchiveFormat) -> (PathBuf, Vec<ArchiveProgress>) {
+        let entries = list(&[photos(dir)], SymlinkPolicy::Skip).expect("list");
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let recorded = reports.clone();
+        let options = ArchiveOptions {
+            format,
+            progress: Some(ProgressHandler::new(move |progress| {
+                recorded.lock().expect("lock").push(progress);
+            })),
+            ..ArchiveOptions::default()
+        };
+        let path = dir.0.join("out");
+        write(
+            File::create(&path).expect("create"),
+            &entries,
+            &options,
+            None,
+            false,
+        )
+        .expect("write");
+        let reports = reports.lock().expect("lock").clone();
+        (path, reports)
+    }
+
+    #[test]
+    fn zip_archives_round_trip() {
+        let dir = TempDir::new("archive");
+        let (path, _) = write_archive(&dir, ArchiveFormat::Zip);
+
+        let mut zip = zip::ZipArchive::new(File::open(path).expect("open")).expect("read zip");
+        let mut names: Vec<String> = zip
+            .file_names()
+            .map(|name| name.expect("entry name").into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "photos/",
+                "photos/2025/",
+                "photos/2025/c.jpg",
+                "photos/a.jpg",
+                "photos/b.jpg"
+            ]
+        );
+        let mut data = String::new();
+        zip.by_name("photos/2025/c.jpg")
+            .expect("entry")
+            .read_to_string(&mut data)
+            .expect("read entry");
+        assert_eq!(data, "data");
+    }
+
+    #[test]
+    fn tar_gz_archives_round_trip() {
+        let dir = TempDir::new("archive");
+        let (path, _) = write_archive(&dir, ArchiveFormat::TarGz);
+
+        let gz = flate2::read::GzDecoder::new(File::open(path).expect("open"));
+        let mut tar = tar::Archive::new(gz);
+        let mut files = Vec::new();
+        for entry in tar.entries().expect("entries") {
+            let mut entry = entry.expect("entry");
+            let mut data = String::new();
+            entry.read_to_string(&mut data).expect("read entry");
+            let name = entry.path().expect("path").to_string_lossy().into_owned();
+            files.push((name, data));
+        }
+        assert_eq!(files[0], ("photos".into(), String::new()));
+        assert_eq!(files[2], ("photos/2025/c.jpg".into(), "data".into()));
+        assert_eq!(files.len(), 5);
+    }
+
+    #[test]
+    fn progress_counts_up_to_the_totals() {
+        let dir = TempDir::new("archive");
+        let (_, reports) = write_archive(&dir, ArchiveFormat::Zip);
+
+        let total = |files_done, bytes_done| ArchiveProgress {
+            files_done,
+            files_total: 3,
+            bytes_done,
+            bytes_total: 12,
+        };
+        assert_eq!(
+            reports,
+            [total(0, 0), total(1, 4), total(2, 8), total(3, 12)]
+        );
+    }
+
+    #[test]
+    fn zip_times_are_utc_calendar_dates() {
+        let at = |secs| zip_time(UNIX_EPOCH + std::time::Duration::from_secs(secs));
+
+        assert_eq!(
+            at(1_792_236_225),
+            DateTime::from_date_and_time(2026, 10, 17, 11, 23, 45).expect("date")
+        );
+        assert_eq!(
+            at(951_782_400),
+            DateTime::from_date_and_time(2000, 2, 29, 0, 0, 0).expect("date")
+        );
+        assert_eq!(at(0), DateTime::DEFAULT);
+    }
+}
@@ -0,0 +1,556 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use tauri::{Runtime, WebviewWindow};
+
+use crate::models::{
+    ShareCapabilities, ShareEmailOptions, ShareFileOptions, ShareFilesOptions, ShareHtmlOptions,
+    ShareItem, ShareOptions, ShareTextOptions, ShareUrlOptions, SharedFile,
+};
+use crate::NativeBackend;
+
+/// Future returned by [`ShareBackend`] methods.
+pub type ShareFuture<'a> = Pin<Box<dyn Future<Output = crate::Result<()>> + Send + 'a>>;
+
+/// Presents shared content to the user.
+///
+/// The plugin uses the platform's built-in backend unless another one is
+/// registered with [`Builder::backend`](crate::Builder::backend). Only
+/// [`share`](Self::share) is required: the other methods describe their
+/// content as [`ShareItem`]s and forward to it unless a backend has a more
+/// specific way to present them. Files are always passed as plain paths,
+/// never as `file://` URLs.
+///
+/// ```
+/// use tauri::{Runtime, WebviewWindow};
+/// use tauri_plugin_sharekit::{ShareBackend, ShareFuture, ShareItem, ShareOptions};
+///
+/// /// Refuses to let files leave the machine.
+/// struct NoFiles<B>(B);
+///
+/// impl<R: Runtime, B: ShareBackend<R>> ShareBackend<R> for NoFiles<B> {
+///     fn share(
+///         &self,
+///         window: WebviewWindow<R>,
+///         items: Vec<ShareItem>,
+///         options: ShareOptions,
+///     ) -> ShareFuture<'_> {
+///         if items.iter().any(|item| matches!(item, ShareItem::File { .. })) {
+///             let err = tauri_plugin_sharekit::Error::InvalidInput("files may not be shared".into());
+///             return Box::pin(async { Err(err) });
+///         }
+///         self.0.share(window, items, options)
+///     }
+/// }
+/// ```
+pub trait ShareBackend<R: Runtime>: Send + Sync + 'static {
+    /// Shares a mix of items. Metadata has already been stripped where
+    /// asked for, so `options.strip_metadata` is always `None`.
+    fn share(
+        &self,
+        window: WebviewWindow<R>,
+        items: Vec<ShareItem>,
+        options: ShareOptions,
+    ) -> ShareFuture<'_>;
+
+    /// What the backend supports. Defaults to every kind of content, since
+    /// [`share`](Self::share) receives all of them, without reporting
+    /// results or dismissals or anchoring the share UI. Backends wrapping
+    /// another one should forward to it.
+    fn capabilities(&self) -> ShareCapabilities {
+        ShareCapabilities {
+            text: true,
+            files: true,
+            multiple_files: true,
+            urls: true,
+            ..ShareCapabilities::default()
+        }
+    }
+
+    /// Shares text, titled with its subject or title.
+    fn share_text(
+        &self,
+        window: WebviewWindow<R>,
+        text: String,
+        options: ShareTextOptions,
+    ) -> ShareFuture<'_> {
+        self.share(
+            window,
+            vec![ShareItem::Text { text }],
+            ShareOptions {
+                title: options.subject.or(options.title),
+                position: options.position,
+                strip_metadata: None,
+            },
+        )
+    }
+
+    /// Shares formatted text along with its plain-text alternative, titled
+    /// like [`share_text`](Self::share_text). The HTML has already been
+    /// sanitized.
+    fn share_html(
+        &self,
+        window: WebviewWindow<R>,
+        html: String,
+        text: String,
+        options: ShareHtmlOptions,
+    ) -> ShareFuture<'_> {
+        self.share(
+            window,
+            vec![ShareItem::Html { html }, ShareItem::Text { text }],
+            ShareOptions {
+                title: options.subject.or(options.title),
+                position: options.position,
+                strip_metadata: None,
+            },
+        )
+    }
+
+    /// Shares a link. Only its title is kept unless a backend can show the
+    /// other options.
+    fn share_url(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        options: ShareUrlOptions,
+    ) -> ShareFuture<'_> {
+        self.share(
+            window,
+            vec![ShareItem::Url { url }],
+            ShareOptions {
+                title: options.title,
+                position: options.position,
+                strip_metadata: None,
+            },
+        )
+    }
+
+    /// Shares a single file. Renaming, archiving, image conversion,
+    /// metadata stripping and encryption have already been applied to the
+    /// file at `url`, so `file_name`, `archive`, `image`, `strip_metadata`
+    /// and `encryption` are always `None`.
+    fn share_file(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        options: ShareFileOptions,
+    ) -> ShareFuture<'_> {
+        self.share(
+            window,
+            vec![ShareItem::File {
+                url,
+                mime_type: options.mime_type,
+            }],
+            ShareOptions {
+                title: options.title,
+                position: options.position,
+                strip_metadata: None,
+            },
+        )
+    }
+
+    /// Shares several files at once. Archiving and metadata stripping have
+    /// already been applied to `files`, so `archive` and `strip_metadata`
+    /// are always `None`.
+    fn share_files(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareFilesOptions,
+    ) -> ShareFuture<'_> {
+        let items = files
+            .into_iter()
+            .map(|file| ShareItem::File {
+                url: file.url,
+                mime_type: file.mime_type,
+            })
+            .collect();
+        self.share(
+            window,
+            items,
+            ShareOptions {
+                title: options.title,
+                position: options.position,
+                strip_metadata: None,
+            },
+        )
+    }
+
+    /// Shares files by email. Recipients are dropped unless a backend can
+    /// address a new email; the body and files are shared like
+    /// [`share`](Self::share) content, with the subject as the title.
+    fn share_email(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareEmailOptions,
+    ) -> ShareFuture<'_> {
+        let (items, options) = email_share(files, options);
+        self.share(window, items, options)
+    }
+}
+
+/// Items and options sharing an email's body and files with
+/// [`ShareBackend::share`].
+pub fn email_share(
+    files: Vec<SharedFile>,
+    options: ShareEmailOptions,
+) -> (Vec<ShareItem>, ShareOptions) {
+    let items = options
+        .body
+        .map(|text| ShareItem::Text { text })
+        .into_iter()
+        .chain(files.into_iter().map(|file| ShareItem::File {
+            url: file.url,
+            mime_type: file.mime_type,
+        }))
+        .collect();
+    let options = ShareOptions {
+        title: options.subject,
+        position: None,
+        strip_metadata: None,
+    };
+    (items, options)
+}
+
+impl<R: Runtime> ShareBackend<R> for Box<dyn ShareBackend<R>> {
+    fn share(
+        &self,
+        window: WebviewWindow<R>,
+        items: Vec<ShareItem>,
+        options: ShareOptions,
+    ) -> ShareFuture<'_> {
+        (**self).share(window, items, options)
+    }
+
+    fn capabilities(&self) -> ShareCapabilities {
+        (**self).capabilities()
+    }
+
+    fn share_text(
+        &self,
+        window: WebviewWindow<R>,
+        text: String,
+        options: ShareTextOptions,
+    ) -> ShareFuture<'_> {
+        (**self).share_text(window, text, options)
+    }
+
+    fn share_html(
+        &self,
+        window: WebviewWindow<R>,
+        html: String,
+        text: String,
+        options: ShareHtmlOptions,
+    ) -> ShareFuture<'_> {
+        (**self).share_html(window, html, text, options)
+    }
+
+    fn share_url(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        options: ShareUrlOptions,
+    ) -> ShareFuture<'_> {
+        (**self).share_url(window, url, options)
+    }
+
+    fn share_file(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        options: ShareFileOptions,
+    ) -> ShareFuture<'_> {
+        (**self).share_file(window, url, options)
+    }
+
+    fn share_files(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareFilesOptions,
+    ) -> ShareFuture<'_> {
+        (**self).share_files(window, files, options)
+    }
+
+    fn share_email(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareEmailOptions,
+    ) -> ShareFuture<'_> {
+        (**self).share_email(window, files, options)
+    }
+}
+
+impl<R: Runtime> ShareBackend<R> for NativeBackend<R> {
+    fn share(
+        &self,
+        window: WebviewWindow<R>,
+        items: Vec<ShareItem>,
+        options: ShareOptions,
+    ) -> ShareFuture<'_> {
+        Box::pin(Self::share(self, window, items, options))
+    }
+
+    fn capabilities(&self) -> ShareCapabilities {
+        Self::capabilities(self)
+    }
+
+    fn share_text(
+        &self,
+        window: WebviewWindow<R>,
+        text: String,
+        options: ShareTextOptions,
+    ) -> ShareFuture<'_> {
+        Box::pin(Self::share_text(self, window, text, options))
+    }
+
+    fn share_html(
+        &self,
+        window: WebviewWindow<R>,
+        html: String,
+        text: String,
+        options: ShareHtmlOptions,
+    ) -> ShareFuture<'_> {
+        Box::pin(Self::share_html(self, window, html, text, options))
+    }
+
+    fn share_url(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        options: ShareUrlOptions,
+    ) -> ShareFuture<'_> {
+        Box::pin(Self::share_url(self, window, url, options))
+    }
+
+    fn share_file(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        options: ShareFileOptions,
+    ) -> ShareFuture<'_> {
+        Box::pin(Self::share_file(self, window, url, options))
+    }
+
+    fn share_files(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareFilesOptions,
+    ) -> ShareFuture<'_> {
+        Box::pin(Self::share_files(self, window, files, options))
+    }
+
+    fn share_email(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareEmailOptions,
+    ) -> ShareFuture<'_> {
+        Box::pin(Self::share_email(self, window, files, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tauri::test::{mock_app, MockRuntime};
+    use tauri::{WebviewUrl, WebviewWindowBuilder};
+
+    use super::*;
+
+    /// Implements only `share` and records what it receives.
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(Vec<ShareItem>, Option<String>)>>);
+
+    impl ShareBackend<MockRuntime> for Recorder {
+        fn share(
+            &self,
+            _window: WebviewWindow<MockRuntime>,
+            items: Vec<ShareItem>,
+            options: ShareOptions,
+        ) -> ShareFuture<'_> {
+            self.0.lock().expect("lock").push((items, options.title));
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    fn window() -> WebviewWindow<MockRuntime> {
+        let app = mock_app();
+        WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+            .build()
+            .expect("mock window")
+    }
+
+    #[tokio::test]
+    async fn default_methods_forward_to_share() {
+        let backend = Recorder::default();
+        let window = window();
+
+        backend
+            .share_text(window.clone(), "Hello".into(), ShareTextOptions::default())
+            .await
+            .expect("share text");
+        backend
+            .share_html(
+                window.clone(),
+                "<b>Hello</b>".into(),
+                "**Hello**".into(),
+                ShareHtmlOptions::default(),
+            )
+            .await
+            .expect("share html");
+        backend
+            .share_url(
+                window.clone(),
+                "https://example.com".into(),
+                ShareUrlOptions {
+                    title: Some("Example".into()),
+                    description: Some("An example".into()),
+                    ..ShareUrlOptions::default()
+                },
+            )
+            .await
+            .expect("share url");
+        backend
+            .share_file(
+                window.clone(),
+                "/tmp/a.pdf".into(),
+                ShareFileOptions {
+                    mime_type: Some("application/pdf".into()),
+                    title: Some("Report".into()),
+                    file_name: None,
+                    position: None,
+                    archive: None,
+                    image: None,
+                    strip_metadata: None,
+                    encryption: None,
+                },
+            )
+            .await
+            .expect("share file");
+        backend
+            .share_files(
+                window,
+                vec![SharedFile {
+                    url: "/tmp/b.png".into(),
+                    mime_type: None,
+                }],
+                ShareFilesOptions::default(),
+            )
+            .await
+            .expect("share files");
+
+        assert_eq!(
+            *backend.0.lock().expect("lock"),
+            vec![
+                (
+                    vec![ShareItem::Text {
+                        text: "Hello".into()
+                    }],
+                    None
+                ),
+                (
+                    vec![
+                        ShareItem::Html {
+                            html: "<b>Hello</b>".into()
+                        },
+                        ShareItem::Text {
+                            text: "**Hello**".into()
+                        },
+                    ],
+                    None
+                ),
+                (
+                    vec![ShareItem::Url {
+                        url: "https://example.com".into()
+                    }],
+                    Some("Example".into())
+                ),
+                (
+                    vec![ShareItem::File {
+                        url: "/tmp/a.pdf".into(),
+                        mime_type: Some("application/pdf".into()),
+                    }],
+                    Some("Report".into())
+                ),
+                (
+                    vec![ShareItem::File {
+                        url: "/tmp/b.png".into(),
+                        mime_type: None,
+                    }],
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn text_is_titled_with_its_subject() {
+        let backend = Recorder::default();
+
+        backend
+            .share_text(
+                window(),
+                "Hello".into(),
+                ShareTextOptions {
+                    title: Some("Greeting".into()),
+                    subject: Some("Hi".into()),
+                    ..ShareTextOptions::default()
+                },
+            )
+            .await
+            .expect("share text");
+
+        assert_eq!(
+            *backend.0.lock().expect("lock"),
+            vec![(
+                vec![ShareItem::Text {
+                    text: "Hello".into()
+                }],
+                Some("Hi".into())
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn email_defaults_to_sharing_the_body_and_files() {
+        let backend = Recorder::default();
+
+        backend
+            .share_email(
+                window(),
+                vec![SharedFile {
+                    url: "/tmp/c.pdf".into(),
+                    mime_type: None,
+                }],
+                ShareEmailOptions {
+                    to: vec!["ana@example.com".into()],
+                    subject: Some("Minutes".into()),
+                    body: Some("Attached".into()),
+                    ..ShareEmailOptions::default()
+                },
+            )
+            .await
+            .expect("share email");
+
+        assert_eq!(
+            *backend.0.lock().expect("lock"),
+            vec![(
+                vec![
+                    ShareItem::Text {
+                        text: "Attached".into()
+                    },
+                    ShareItem::File {
+                        url: "/tmp/c.pdf".into(),
+                        mime_type: None,
+                    },
+                ],
+                Some("Minutes".into())
+            )]
+        );
+    }
+}
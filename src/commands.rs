@@ -1,8 +1,19 @@
+use tauri::image::Image;
+use tauri::ipc::{Channel, CommandScope, GlobalScope, JavaScriptChannelId, Request};
 use tauri::AppHandle;
 use tauri::{command, Runtime, WebviewWindow};
 
-use crate::models::{ShareFileOptions, SharePosition, ShareTextOptions};
-use crate::ShareExt;
+use crate::archive::{self, EntryKind};
+use crate::data::{DataOptions, ImageDataOptions};
+use crate::imaging;
+use crate::models::{
+    ArchiveOptions, ArchiveProgress, ImageOptions, ProgressHandler, ShareCapabilities,
+    ShareEmailOptions, ShareFileOptions, ShareFilesOptions, ShareHtmlOptions, ShareItem,
+    ShareOptions, SharePosition, ShareTextOptions, ShareUrlOptions, SharedFile,
+};
+use crate::scope::{ensure_allowed, ensure_paths_allowed, file_path, resolve_allowed, Entry};
+use crate::web_share::WebShareData;
+use crate::{Encryption, Error, ShareExt};
 
 #[command]
 pub async fn share_text<R: Runtime>(
@@ -10,8 +21,10 @@ pub async fn share_text<R: Runtime>(
     window: WebviewWindow<R>,
     text: String,
     mime_type: Option<String>,
+    title: Option<String>,
+    subject: Option<String>,
     position: Option<SharePosition>,
-) -> Result<(), String> {
+) -> crate::Result<()> {
     app_handle
         .share()
         .share_text(
@@ -19,22 +32,103 @@ pub async fn share_text<R: Runtime>(
             text,
             ShareTextOptions {
                 mime_type,
+                title,
+                subject,
                 position,
             },
         )
         .await
-        .map_err(|e| e.to_string())
 }
 
 #[command]
-pub async fn share_file<R: Runtime>(
+pub async fn share_html<R: Runtime>(
     app_handle: AppHandle<R>,
     window: WebviewWindow<R>,
+    html: String,
+    text: Option<String>,
+    title: Option<String>,
+    subject: Option<String>,
+    position: Option<SharePosition>,
+) -> crate::Result<()> {
+    app_handle
+        .share()
+        .share_html(
+            window,
+            &html,
+            text,
+            ShareHtmlOptions {
+                title,
+                subject,
+                position,
+            },
+        )
+        .await
+}
+
+/// Shares a link. The thumbnail is a file read on the webview's behalf, so
+/// it has to be in scope.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn share_url<R: Runtime>(
+    app_handle: AppHandle<R>,
+    window: WebviewWindow<R>,
+    command_scope: CommandScope<Entry>,
+    global_scope: GlobalScope<Entry>,
     url: String,
+    title: Option<String>,
+    description: Option<String>,
+    mut thumbnail: Option<String>,
+    position: Option<SharePosition>,
+) -> crate::Result<()> {
+    resolve_allowed(
+        &app_handle,
+        &command_scope,
+        &global_scope,
+        thumbnail.as_mut(),
+    )?;
+    app_handle
+        .share()
+        .share_url(
+            window,
+            &url,
+            ShareUrlOptions {
+                title,
+                description,
+                thumbnail,
+                position,
+            },
+        )
+        .await
+}
+
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn share_file<R: Runtime>(
+    app_handle: AppHandle<R>,
+    window: WebviewWindow<R>,
+    command_scope: CommandScope<Entry>,
+    global_scope: GlobalScope<Entry>,
+    mut url: String,
     mime_type: Option<String>,
     title: Option<String>,
+    file_name: Option<String>,
     position: Option<SharePosition>,
-) -> Result<(), String> {
+    mut archive: Option<ArchiveOptions>,
+    on_progress: Option<JavaScriptChannelId>,
+    image: Option<ImageOptions>,
+    strip_metadata: Option<bool>,
+    encryption: Option<String>,
+) -> crate::Result<()> {
+    let encryption = named_encryption(&app_handle, encryption)?;
+    let on_progress = on_progress.map(|id| id.channel_on(window.as_ref().clone()));
+    ensure_shared_allowed(
+        &app_handle,
+        &command_scope,
+        &global_scope,
+        [&mut url],
+        &mut archive,
+        on_progress,
+    )?;
     app_handle
         .share()
         .share_file(
@@ -42,10 +136,279 @@ pub async fn share_file<R: Runtime>(
             url,
             ShareFileOptions {
                 mime_type,
+                title,
+                file_name,
+                position,
+                archive,
+                image,
+                strip_metadata,
+                encryption,
+            },
+        )
+        .await
+}
+
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn share_files<R: Runtime>(
+    app_handle: AppHandle<R>,
+    window: WebviewWindow<R>,
+    command_scope: CommandScope<Entry>,
+    global_scope: GlobalScope<Entry>,
+    mut files: Vec<SharedFile>,
+    title: Option<String>,
+    position: Option<SharePosition>,
+    mut archive: Option<ArchiveOptions>,
+    on_progress: Option<JavaScriptChannelId>,
+    strip_metadata: Option<bool>,
+) -> crate::Result<()> {
+    let on_progress = on_progress.map(|id| id.channel_on(window.as_ref().clone()));
+    ensure_shared_allowed(
+        &app_handle,
+        &command_scope,
+        &global_scope,
+        files.iter_mut().map(|file| &mut file.url),
+        &mut archive,
+        on_progress,
+    )?;
+    app_handle
+        .share()
+        .share_files(
+            window,
+            files,
+            ShareFilesOptions {
+                title,
+                position,
+                archive,
+                strip_metadata,
+            },
+        )
+        .await
+}
+
+/// Shares files by email. The attachments are read on the webview's behalf,
+/// so they have to be in scope.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn share_email<R: Runtime>(
+    app_handle: AppHandle<R>,
+    window: WebviewWindow<R>,
+    command_scope: CommandScope<Entry>,
+    global_scope: GlobalScope<Entry>,
+    mut files: Vec<SharedFile>,
+    to: Option<Vec<String>>,
+    cc: Option<Vec<String>>,
+    subject: Option<String>,
+    body: Option<String>,
+) -> crate::Result<()> {
+    resolve_allowed(
+        &app_handle,
+        &command_scope,
+        &global_scope,
+        files.iter_mut().map(|file| &mut file.url),
+    )?;
+    app_handle
+        .share()
+        .share_email(
+            window,
+            files,
+            ShareEmailOptions {
+                to: to.unwrap_or_default(),
+                cc: cc.unwrap_or_default(),
+                subject,
+                body,
+            },
+        )
+        .await
+}
+
+/// Checks the files read when sharing `urls` against the scopes: the files
+/// themselves or, when they are packed into an archive, every file that
+/// goes into it. Each URL is replaced with the canonical path that was
+/// checked, like [`resolve_allowed`] does. An archive gets `on_progress` as
+/// its progress handler.
+fn ensure_shared_allowed<'a, R: Runtime>(
+    app: &AppHandle<R>,
+    command_scope: &CommandScope<Entry>,
+    global_scope: &GlobalScope<Entry>,
+    urls: impl IntoIterator<Item = &'a mut String>,
+    archive: &mut Option<ArchiveOptions>,
+    on_progress: Option<Channel<ArchiveProgress>>,
+) -> crate::Result<()> {
+    let mut urls: Vec<_> = urls.into_iter().collect();
+    let paths: Vec<_> = urls.iter().map(|url| file_path(url)).collect();
+    if !archive::is_needed(&paths, archive.as_ref()) {
+        return resolve_allowed(app, command_scope, global_scope, urls);
+    }
+
+    let paths = paths
+        .iter()
+        .map(std::fs::canonicalize)
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let options = archive.get_or_insert_default();
+    let files: Vec<_> = archive::list(&paths, options.symlinks)?
+        .into_iter()
+        .filter(|entry| matches!(entry.kind, EntryKind::File { .. }))
+        .map(|entry| entry.source)
+        .collect();
+    ensure_paths_allowed(app, command_scope, global_scope, &files)?;
+    for (url, path) in urls.iter_mut().zip(&paths) {
+        **url = path.to_string_lossy().into_owned();
+    }
+    if let Some(channel) = on_progress {
+        options.progress = Some(ProgressHandler::new(move |progress| {
+            let _ = channel.send(progress);
+        }));
+    }
+    Ok(())
+}
+
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn share<R: Runtime>(
+    app_handle: AppHandle<R>,
+    window: WebviewWindow<R>,
+    command_scope: CommandScope<Entry>,
+    global_scope: GlobalScope<Entry>,
+    mut items: Vec<ShareItem>,
+    title: Option<String>,
+    position: Option<SharePosition>,
+    strip_metadata: Option<bool>,
+) -> crate::Result<()> {
+    resolve_allowed(
+        &app_handle,
+        &command_scope,
+        &global_scope,
+        items.iter_mut().filter_map(|item| match item {
+            ShareItem::File { url, .. } => Some(url),
+            _ => None,
+        }),
+    )?;
+    app_handle
+        .share()
+        .share(
+            window,
+            items,
+            ShareOptions {
                 title,
                 position,
+                strip_metadata,
             },
         )
         .await
-        .map_err(|e| e.to_string())
+}
+
+/// Shares bytes sent either as a raw IPC body, with the options in the
+/// [`OPTIONS_HEADER`](crate::data::OPTIONS_HEADER) header, or as JSON whose
+/// `data` is base64, a `data:` URL or an array of bytes.
+#[command]
+pub async fn share_data<R: Runtime>(
+    app_handle: AppHandle<R>,
+    window: WebviewWindow<R>,
+    request: Request<'_>,
+) -> crate::Result<()> {
+    let (data, mut options) = crate::data::parse_request::<DataOptions>(&request)?;
+    options.options.encryption = named_encryption(&app_handle, options.encryption)?;
+    app_handle
+        .share()
+        .share_data(window, &data, options.options)
+        .await
+}
+
+/// Shares an image sent like [`share_data`] content: raw RGBA pixels along
+/// with their `width` and `height`, or an encoded JPEG, PNG, WebP or GIF
+/// image.
+#[command]
+pub async fn share_image<R: Runtime>(
+    app_handle: AppHandle<R>,
+    window: WebviewWindow<R>,
+    request: Request<'_>,
+) -> crate::Result<()> {
+    let (data, options) = crate::data::parse_request::<ImageDataOptions>(&request)?;
+    let image = match (options.width, options.height) {
+        (Some(width), Some(height)) => Image::new_owned(data.into_owned(), width, height),
+        (None, None) => {
+            let data = data.into_owned();
+            let rgba = tauri::async_runtime::spawn_blocking(move || imaging::decode_rgba(&data))
+                .await
+                .map_err(std::io::Error::other)??;
+            let (width, height) = rgba.dimensions();
+            Image::new_owned(rgba.into_raw(), width, height)
+        }
+        _ => {
+            return Err(Error::InvalidInput(
+                "`width` and `height` must be passed together".into(),
+            ))
+        }
+    };
+    app_handle
+        .share()
+        .share_image(window, image, options.options)
+        .await
+}
+
+/// Shares the `ShareData` passed to the `navigator.share` polyfill. The
+/// content of its files is the request body.
+#[command]
+pub async fn share_web<R: Runtime>(
+    app_handle: AppHandle<R>,
+    window: WebviewWindow<R>,
+    request: Request<'_>,
+) -> crate::Result<()> {
+    let (data, web) = crate::data::parse_request::<WebShareData>(&request)?;
+    let share = app_handle.share();
+    let (items, title) = web.into_items(&data, &share.config().mime_types)?;
+    share
+        .share(
+            window,
+            items,
+            ShareOptions {
+                title,
+                ..ShareOptions::default()
+            },
+        )
+        .await
+}
+
+#[command]
+#[allow(clippy::needless_pass_by_value)] // commands receive their arguments by value
+pub fn get_capabilities<R: Runtime>(app_handle: AppHandle<R>) -> ShareCapabilities {
+    app_handle.share().capabilities()
+}
+
+/// Whether `items` can be shared: the backend supports them and the files
+/// among them are in scope. Nothing is shown to the user.
+#[command]
+#[allow(clippy::needless_pass_by_value)] // commands receive their arguments by value
+pub fn can_share<R: Runtime>(
+    app_handle: AppHandle<R>,
+    command_scope: CommandScope<Entry>,
+    global_scope: GlobalScope<Entry>,
+    items: Vec<ShareItem>,
+) -> bool {
+    let in_scope = ensure_allowed(
+        &app_handle,
+        &command_scope,
+        &global_scope,
+        items.iter().filter_map(|item| match item {
+            ShareItem::File { url, .. } => Some(url.as_str()),
+            _ => None,
+        }),
+    );
+    in_scope.is_ok() && app_handle.share().can_share(&items)
+}
+
+/// Looks up the encryption the webview picked by name. Unknown names fail
+/// rather than sharing the content unencrypted.
+fn named_encryption<R: Runtime>(
+    app: &AppHandle<R>,
+    name: Option<String>,
+) -> crate::Result<Option<Encryption>> {
+    name.map(|name| {
+        app.share()
+            .encryption(&name)
+            .cloned()
+            .ok_or_else(|| Error::InvalidInput(format!("unknown encryption `{name}`")))
+    })
+    .transpose()
 }
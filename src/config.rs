@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::Error;
+
+/// Shortest `stagingTtl`, in seconds. Staged copies have to outlive the
+/// share UI reading them.
+const MIN_STAGING_TTL: u64 = 60;
+
+/// Settings read from `plugins.sharekit` in `tauri.conf.json`.
+///
+/// ```json
+/// {
+///   "plugins": {
+///     "sharekit": {
+///       "defaultTitle": "Shared from My App",
+///       "stagingDir": "$APPCACHE/outgoing",
+///       "stagingTtl": 600,
+///       "mimeTypes": { "sketch": "application/x-sketch" },
+///       "stripMetadata": true,
+///       "scope": { "allow": ["$DOCUMENT/**"], "deny": ["$DOCUMENT/private/**"] },
+///       "linux": { "backend": "portal" },
+///       "fallback": "openDefault"
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Config {
+    /// Title used when a share call does not pass one.
+    #[serde(default)]
+    pub default_title: Option<String>,
+    /// Directory in which a `sharekit` directory for temporary copies of
+    /// shared content is created. Defaults to `$APPCACHE`.
+    #[serde(default)]
+    pub staging_dir: Option<PathBuf>,
+    /// Seconds after which temporary copies are deleted, at least 60.
+    /// Defaults to one hour.
+    #[serde(default)]
+    pub staging_ttl: Option<u64>,
+    /// MIME types for file extensions, used instead of the built-in table
+    /// when a share does not pass a type and the content is not recognised.
+    #[serde(default)]
+    pub mime_types: BTreeMap<String, String>,
+    /// Whether shared files are replaced by copies without privacy metadata
+    /// when a share call does not say. See
+    /// [`ShareFileOptions::strip_metadata`](crate::ShareFileOptions::strip_metadata).
+    #[serde(default)]
+    pub strip_metadata: bool,
+    /// Files the webview may share, in addition to the capability scopes.
+    #[serde(default)]
+    pub scope: ScopeConfig,
+    /// Linux specific settings.
+    #[serde(default)]
+    pub linux: LinuxConfig,
+    /// What to do when the share UI cannot be shown.
+    #[serde(default)]
+    pub fallback: Fallback,
+}
+
+/// Allow and deny path patterns, with the same syntax as capability scopes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScopeConfig {
+    #[serde(default)]
+    pub allow: Vec<PathBuf>,
+    #[serde(default)]
+    pub deny: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinuxConfig {
+    /// Mechanism used to hand content to other applications.
+    #[serde(default)]
+    pub backend: LinuxBackend,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LinuxBackend {
+    /// The `OpenURI` and `Email` xdg-desktop-portal interfaces.
+    #[default]
+    Portal,
+    /// The `xdg-open` and `xdg-email` command line tools.
+    XdgUtils,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Fallback {
+    /// Fail with the backend's error.
+    #[default]
+    Error,
+    /// Open a lone link or file with the default application instead. Only
+    /// the Linux backend can fail this way; other platforms always have a
+    /// share UI.
+    OpenDefault,
+}
+
+impl Config {
+    /// Resolves base directory variables such as `$APPCACHE` in the
+    /// configured paths and normalizes `mimeTypes` keys to lowercase
+    /// extensions without a dot.
+    ///
+    /// Scope patterns using a base directory that does not exist on this
+    /// platform are dropped, like capability scope entries are. A
+    /// `stagingTtl` below 60 seconds is rejected, since copies could be
+    /// deleted before the share UI has read them.
+    pub(crate) fn resolve<R: Runtime>(mut self, app: &AppHandle<R>) -> crate::Result<Self> {
+        if let Some(ttl) = self.staging_ttl.filter(|&ttl| ttl < MIN_STAGING_TTL) {
+            return Err(Error::Config(format!(
+                "stagingTtl must be at least {MIN_STAGING_TTL} seconds, got {ttl}"
+            )));
+        }
+        if let Some(dir) = self.staging_dir.take() {
+            let resolved = app.path().parse(&dir).map_err(|e| {
+                Error::Config(format!("invalid stagingDir `{}`: {e}", dir.display()))
+            })?;
+            self.staging_dir = Some(resolved);
+        }
+        self.mime_types = std::mem::take(&mut self.mime_types)
+            .into_iter()
+            .map(|(extension, mime)| {
+                let extension = extension.trim_start_matches('.').to_ascii_lowercase();
+                (extension, mime)
+            })
+            .collect();
+        self.scope.allow = resolve_patterns(app, self.scope.allow)?;
+        self.scope.deny = resolve_patterns(app, self.scope.deny)?;
+        Ok(self)
+    }
+
+    /// `strip`, or the configured `stripMetadata` when it is `None`.
+    #[must_use]
+    pub fn strip_metadata(&self, strip: Option<bool>) -> bool {
+        strip.unwrap_or(self.strip_metadata)
+    }
+
+    /// `title`, or the configured `defaultTitle` when it is `None`.
+    #[must_use]
+    pub fn title(&self, title: Option<String>) -> Option<String> {
+        title.or_else(|| self.default_title.clone())
+    }
+}
+
+fn resolve_patterns<R: Runtime>(
+    app: &AppHandle<R>,
+    patterns: Vec<PathBuf>,
+) -> crate::Result<Vec<PathBuf>> {
+    let mut resolved = Vec::with_capacity(patterns.len());
+    for pattern in patterns {
+        match app.path().parse(&pattern) {
+            Ok(path) => resolved.push(path),
+            #[cfg(not(target_os = "android"))]
+            Err(tauri::Error::UnknownPath) => {}
+            Err(e) => {
+                return Err(Error::Config(format!(
+                    "invalid scope pattern `{}`: {e}",
+                    pattern.display()
+                )))
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn parse(value: serde_json::Value) -> Result<Config, String> {
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn empty_object_uses_defaults() {
+        assert_eq!(parse(json!({})), Ok(Config::default()));
+        assert_eq!(Config::default().linux.backend, LinuxBackend::Portal);
+        assert_eq!(Config::default().fallback, Fallback::Error);
+    }
+
+    #[test]
+    fn missing_section_uses_defaults() {
+        let config: Option<Config> = serde_json::from_value(json!(null)).expect("parse");
+        assert_eq!(config.unwrap_or_default(), Config::default());
+    }
+
+    #[test]
+    fn parses_every_setting() {
+        let config = parse(json!({
+            "defaultTitle": "Shared from Demo",
+            "stagingDir": "$APPCACHE/outgoing",
+            "stagingTtl": 600,
+            "mimeTypes": { "sketch": "application/x-sketch" },
+            "stripMetadata": true,
+            "scope": { "allow": ["$DOCUMENT/**"], "deny": ["$DOCUMENT/private/**"] },
+            "linux": { "backend": "xdgUtils" },
+            "fallback": "openDefault",
+        }))
+        .expect("parse");
+
+        assert_eq!(
+            config,
+            Config {
+                default_title: Some("Shared from Demo".into()),
+                staging_dir: Some("$APPCACHE/outgoing".into()),
+                staging_ttl: Some(600),
+                mime_types: BTreeMap::from([("sketch".into(), "application/x-sketch".into())]),
+                strip_metadata: true,
+                scope: ScopeConfig {
+                    allow: vec!["$DOCUMENT/**".into()],
+                    deny: vec!["$DOCUMENT/private/**".into()],
+                },
+                linux: LinuxConfig {
+                    backend: LinuxBackend::XdgUtils,
+                },
+                fallback: Fallback::OpenDefault,
+            }
+        );
+    }
+
+    #[test]
+    fn partial_sections_fill_in_defaults() {
+        let config =
+            parse(json!({ "scope": { "allow": ["/tmp/*"] }, "linux": {} })).expect("parse");
+        assert_eq!(config.scope.allow, vec![PathBuf::from("/tmp/*")]);
+        assert!(config.scope.deny.is_empty());
+        assert_eq!(config.linux.backend, LinuxBackend::Portal);
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let err = parse(json!({ "defaultTitel": "typo" })).expect_err("typo");
+        assert!(err.contains("unknown field `defaultTitel`"), "{err}");
+
+        let err = parse(json!({ "linux": { "backnd": "portal" } })).expect_err("typo");
+        assert!(err.contains("unknown field `backnd`"), "{err}");
+    }
+
+    #[test]
+    fn unknown_variants_list_the_accepted_values() {
+        let err = parse(json!({ "linux": { "backend": "kde" } })).expect_err("bad backend");
+        assert!(err.contains("unknown variant `kde`"), "{err}");
+        assert!(err.contains("expected `portal` or `xdgUtils`"), "{err}");
+
+        let err = parse(json!({ "fallback": "clipboard" })).expect_err("bad fallback");
+        assert!(err.contains("expected `error` or `openDefault`"), "{err}");
+    }
+
+    #[test]
+    fn wrong_types_are_rejected() {
+        let err = parse(json!({ "defaultTitle": 42 })).expect_err("number title");
+        assert!(err.contains("invalid type: integer `42`"), "{err}");
+
+        let err = parse(json!({ "scope": { "allow": "$DOCUMENT/**" } })).expect_err("string");
+        assert!(err.contains("expected a sequence"), "{err}");
+    }
+
+    #[test]
+    fn default_title_only_fills_missing_titles() {
+        let config = Config {
+            default_title: Some("Default".into()),
+            ..Config::default()
+        };
+        assert_eq!(config.title(None), Some("Default".into()));
+        assert_eq!(config.title(Some("Own".into())), Some("Own".into()));
+        assert_eq!(Config::default().title(None), None);
+    }
+
+    #[test]
+    fn short_staging_ttls_are_rejected() {
+        let app = tauri::test::mock_app();
+        let resolve = |staging_ttl| {
+            Config {
+                staging_ttl,
+                ..Config::default()
+            }
+            .resolve(app.handle())
+        };
+
+        assert!(matches!(
+            resolve(Some(0)),
+            Err(Error::Config(message)) if message.contains("at least 60")
+        ));
+        assert!(resolve(Some(60)).is_ok());
+        assert!(resolve(None).is_ok());
+    }
+
+    #[test]
+    fn resolve_expands_base_directories() {
+        let app = tauri::test::mock_app();
+        let config = Config {
+            staging_dir: Some("$APPCACHE/outgoing".into()),
+            mime_types: BTreeMap::from([(".Sketch".into(), "application/x-sketch".into())]),
+            scope: ScopeConfig {
+                allow: vec!["$APPDATA/**".into(), "/srv/share/*".into()],
+                deny: Vec::new(),
+            },
+            ..Config::default()
+        }
+        .resolve(app.handle())
+        .expect("resolve");
+
+        let cache = app.path().app_cache_dir().expect("cache dir");
+        let data = app.path().app_data_dir().expect("data dir");
+        assert_eq!(config.staging_dir, Some(cache.join("outgoing")));
+        assert_eq!(
+            config.mime_types,
+            BTreeMap::from([("sketch".into(), "application/x-sketch".into())])
+        );
+        assert_eq!(
+            config.scope.allow,
+            vec![data.join("**"), PathBuf::from("/srv/share/*")]
+        );
+    }
+}
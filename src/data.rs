@@ -0,0 +1,253 @@
+use std::borrow::Cow;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use percent_encoding::percent_decode_str;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tauri::ipc::{InvokeBody, Request};
+
+use crate::models::{ShareDataOptions, ShareImageOptions};
+use crate::Error;
+
+/// Header carrying the options of a raw `share_data`, `share_image` or
+/// `share_web` body, as percent-encoded JSON.
+pub const OPTIONS_HEADER: &str = "x-sharekit-options";
+
+/// `data` of a JSON `share_data` or `share_image` body.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Data {
+    /// Base64 or a `data:` URL.
+    Encoded(String),
+    Bytes(Vec<u8>),
+}
+
+/// Options of a `share_data` invocation.
+#[derive(Debug, Deserialize)]
+pub struct DataOptions {
+    #[serde(flatten)]
+    pub options: ShareDataOptions,
+    /// Name of an encryption registered with
+    /// [`Builder::encryption`](crate::Builder::encryption).
+    pub encryption: Option<String>,
+}
+
+/// Options of a `share_image` invocation.
+#[derive(Debug, Deserialize)]
+pub struct ImageDataOptions {
+    #[serde(flatten)]
+    pub options: ShareImageOptions,
+    /// Size of the image when the content is raw RGBA pixels rather than an
+    /// encoded image.
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// Options sent along with content.
+pub trait BodyOptions: DeserializeOwned {
+    /// Receives the media type of content sent as a `data:` URL.
+    fn media_type(&mut self, _media_type: String) {}
+}
+
+impl BodyOptions for DataOptions {
+    fn media_type(&mut self, media_type: String) {
+        self.options.mime_type.get_or_insert(media_type);
+    }
+}
+
+impl BodyOptions for ImageDataOptions {}
+
+#[derive(Deserialize)]
+struct JsonBody<T> {
+    data: Data,
+    #[serde(flatten)]
+    options: T,
+}
+
+/// Extracts the content and options of a `share_data`, `share_image` or
+/// `share_web` invocation.
+///
+/// Raw bodies are borrowed, so large blobs are not copied before they are
+/// staged.
+pub fn parse_request<'a, T: BodyOptions>(
+    request: &'a Request<'_>,
+) -> crate::Result<(Cow<'a, [u8]>, T)> {
+    match request.body() {
+        InvokeBody::Raw(data) => {
+            let header = request
+                .headers()
+                .get(OPTIONS_HEADER)
+                .ok_or_else(|| invalid(format!("missing `{OPTIONS_HEADER}` header")))?
+                .to_str()
+                .map_err(|e| invalid(format!("invalid `{OPTIONS_HEADER}` header: {e}")))?;
+            Ok((Cow::Borrowed(data), parse_options(header)?))
+        }
+        InvokeBody::Json(value) => {
+            let (data, options) = parse_json(value)?;
+            Ok((Cow::Owned(data), options))
+        }
+    }
+}
+
+fn parse_json<T: BodyOptions>(value: &serde_json::Value) -> crate::Result<(Vec<u8>, T)> {
+    let body = JsonBody::<T>::deserialize(value).map_err(|e| invalid(e.to_string()))?;
+    let mut options = body.options;
+    let data = match body.data {
+        Data::Bytes(data) => data,
+        Data::Encoded(text) => {
+            let (data, media_type) = decode(&text)?;
+            if let Some(media_type) = media_type {
+                options.media_type(media_type);
+            }
+            data
+        }
+    };
+    Ok((data, options))
+}
+
+fn parse_options<T: BodyOptions>(header: &str) -> crate::Result<T> {
+    let json = percent_decode_str(header)
+        .decode_utf8()
+        .map_err(|e| invalid(format!("invalid `{OPTIONS_HEADER}` header: {e}")))?;
+    serde_json::from_str(&json).map_err(|e| invalid(format!("invalid share options: {e}")))
+}
+
+/// Decodes base64 or a `data:` URL, returning the URL's media type if it has
+/// one.
+fn decode(text: &str) -> crate::Result<(Vec<u8>, Option<String>)> {
+    let Some(url) = text.strip_prefix("data:") else {
+        return Ok((decode_base64(text)?, None));
+    };
+    let (meta, payload) = url
+        .split_once(',')
+        .ok_or_else(|| invalid("data URL without `,`".into()))?;
+    let (meta, is_base64) = meta
+        .strip_suffix(";base64")
+        .map_or((meta, false), |meta| (meta, true));
+    let media_type = meta.split(';').next().filter(|m| !m.is_empty());
+
+    let data = if is_base64 {
+        decode_base64(payload)?
+    } else {
+        percent_decode_str(payload).collect()
+    };
+    Ok((data, media_type.map(ToOwned::to_owned)))
+}
+
+fn decode_base64(text: &str) -> crate::Result<Vec<u8>> {
+    STANDARD
+        .decode(text)
+        .map_err(|e| invalid(format!("invalid base64 data: {e}")))
+}
+
+const fn invalid(message: String) -> Error {
+    Error::InvalidInput(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn plain_base64_has_no_media_type() {
+        assert_eq!(decode("aGk=").expect("decode"), (b"hi".to_vec(), None));
+    }
+
+    #[test]
+    fn data_urls_carry_their_media_type() {
+        assert_eq!(
+            decode("data:image/png;base64,AAEC").expect("decode"),
+            (vec![0, 1, 2], Some("image/png".into()))
+        );
+        assert_eq!(
+            decode("data:text/plain;charset=utf-8,a%20b").expect("decode"),
+            (b"a b".to_vec(), Some("text/plain".into()))
+        );
+        assert_eq!(decode("data:,hi").expect("decode"), (b"hi".to_vec(), None));
+    }
+
+    #[test]
+    fn malformed_data_is_invalid_input() {
+        assert!(matches!(decode("not base64!"), Err(Error::InvalidInput(_))));
+        assert!(matches!(
+            decode("data:image/png;base64"),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn options_header_is_percent_encoded_json() {
+        let header = "%7B%22name%22%3A%22Caf%C3%A9.png%22%2C%22mimeType%22%3A%22image%2Fpng%22%7D";
+        assert_eq!(
+            parse_options::<DataOptions>(header).expect("parse").options,
+            ShareDataOptions {
+                name: "Café.png".into(),
+                mime_type: Some("image/png".into()),
+                ..ShareDataOptions::default()
+            }
+        );
+        assert!(matches!(
+            parse_options::<DataOptions>("%7B%7D"),
+            Err(Error::InvalidInput(message)) if message.contains("missing field `name`")
+        ));
+    }
+
+    #[test]
+    fn json_bodies_accept_every_encoding() {
+        for data in [json!("aGk="), json!("data:,hi"), json!([104, 105])] {
+            let (bytes, options): (_, DataOptions) =
+                parse_json(&json!({ "data": data, "name": "hi.txt" })).expect("parse");
+            assert_eq!(bytes, b"hi");
+            assert_eq!(options.options.name, "hi.txt");
+        }
+    }
+
+    #[test]
+    fn explicit_mime_type_wins_over_data_url() {
+        let (_, options): (_, DataOptions) = parse_json(&json!({
+            "data": "data:text/plain,hi",
+            "name": "hi.md",
+            "mimeType": "text/markdown",
+        }))
+        .expect("parse");
+        assert_eq!(options.options.mime_type.as_deref(), Some("text/markdown"));
+
+        let (_, options): (_, DataOptions) =
+            parse_json(&json!({ "data": "data:text/plain,hi", "name": "hi.txt" })).expect("parse");
+        assert_eq!(options.options.mime_type.as_deref(), Some("text/plain"));
+    }
+
+    #[test]
+    fn encryption_is_picked_by_name() {
+        let (_, options): (_, DataOptions) = parse_json(&json!({
+            "data": "aGk=",
+            "name": "record.pdf",
+            "encryption": "clinic",
+        }))
+        .expect("parse");
+        assert_eq!(options.encryption.as_deref(), Some("clinic"));
+        assert_eq!(options.options.encryption, None);
+    }
+
+    #[test]
+    fn image_bodies_carry_their_size() {
+        let (data, options): (_, ImageDataOptions) = parse_json(&json!({
+            "data": [255, 0, 0, 255],
+            "width": 1,
+            "height": 1,
+            "format": "jpeg",
+            "name": "Chart",
+        }))
+        .expect("parse");
+        assert_eq!(data, [255, 0, 0, 255]);
+        assert_eq!((options.width, options.height), (Some(1), Some(1)));
+        assert_eq!(options.options.name.as_deref(), Some("Chart"));
+
+        let options: ImageDataOptions = parse_options("%7B%7D").expect("parse");
+        assert_eq!((options.width, options.height), (None, None));
+        assert_eq!(options.options, ShareImageOptions::default());
+    }
+}
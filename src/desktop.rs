@@ -1,22 +1,47 @@
 use serde::de::DeserializeOwned;
 use tauri::{plugin::PluginApi, AppHandle, Runtime, WebviewWindow};
 
-use crate::models::{ShareFileOptions, ShareTextOptions};
+use crate::config::Config;
+use crate::models::{
+    ShareCapabilities, ShareEmailOptions, ShareFileOptions, ShareFilesOptions, ShareHtmlOptions,
+    ShareItem, ShareOptions, ShareTextOptions, ShareUrlOptions, SharedFile,
+};
 
 #[allow(clippy::unnecessary_wraps)] // signature required by `lib.rs` plugin setup contract
 pub fn init<R: Runtime, C: DeserializeOwned>(
     app: &AppHandle<R>,
     _api: PluginApi<R, C>,
-) -> crate::Result<ShareKit<R>> {
-    Ok(ShareKit(app.clone()))
+    config: Config,
+) -> crate::Result<NativeBackend<R>> {
+    Ok(NativeBackend {
+        app: app.clone(),
+        config,
+    })
 }
 
-/// Access to the share APIs.
-pub struct ShareKit<R: Runtime>(AppHandle<R>);
+/// Fallback backend for desktop platforms without a share UI.
+pub struct NativeBackend<R: Runtime> {
+    app: AppHandle<R>,
+    config: Config,
+}
 
 // `async` keyword on share_text/share_file is required so `commands.rs` can `.await`
 // the call uniformly across platforms; on this stub no await is needed.
-impl<R: Runtime> ShareKit<R> {
+impl<R: Runtime> NativeBackend<R> {
+    /// Nothing can be shared.
+    #[allow(clippy::unused_self)] // same signature as the backends that depend on their config
+    pub const fn capabilities(&self) -> ShareCapabilities {
+        ShareCapabilities {
+            text: false,
+            files: false,
+            multiple_files: false,
+            urls: false,
+            reports_result: false,
+            anchors_position: false,
+            reports_dismissal: false,
+        }
+    }
+
     #[allow(clippy::unused_async)]
     pub async fn share_text(
         &self,
@@ -27,6 +52,27 @@ impl<R: Runtime> ShareKit<R> {
         Err(crate::Error::UnsupportedPlatform)
     }
 
+    #[allow(clippy::unused_async)]
+    pub async fn share_html(
+        &self,
+        _window: WebviewWindow<R>,
+        _html: String,
+        _text: String,
+        _options: ShareHtmlOptions,
+    ) -> crate::Result<()> {
+        Err(crate::Error::UnsupportedPlatform)
+    }
+
+    #[allow(clippy::unused_async)]
+    pub async fn share_url(
+        &self,
+        _window: WebviewWindow<R>,
+        _url: String,
+        _options: ShareUrlOptions,
+    ) -> crate::Result<()> {
+        Err(crate::Error::UnsupportedPlatform)
+    }
+
     #[allow(clippy::unused_async)]
     pub async fn share_file(
         &self,
@@ -36,4 +82,34 @@ impl<R: Runtime> ShareKit<R> {
     ) -> crate::Result<()> {
         Err(crate::Error::UnsupportedPlatform)
     }
+
+    #[allow(clippy::unused_async)]
+    pub async fn share_files(
+        &self,
+        _window: WebviewWindow<R>,
+        _files: Vec<SharedFile>,
+        _options: ShareFilesOptions,
+    ) -> crate::Result<()> {
+        Err(crate::Error::UnsupportedPlatform)
+    }
+
+    #[allow(clippy::unused_async)]
+    pub async fn share_email(
+        &self,
+        _window: WebviewWindow<R>,
+        _files: Vec<SharedFile>,
+        _options: ShareEmailOptions,
+    ) -> crate::Result<()> {
+        Err(crate::Error::UnsupportedPlatform)
+    }
+
+    #[allow(clippy::unused_async)]
+    pub async fn share(
+        &self,
+        _window: WebviewWindow<R>,
+        _items: Vec<ShareItem>,
+        _options: ShareOptions,
+    ) -> crate::Result<()> {
+        Err(crate::Error::UnsupportedPlatform)
+    }
 }
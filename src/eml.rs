@@ -0,0 +1,467 @@
+// Only the Windows and macOS backends write messages.
+#![cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+
+use std::collections::hash_map::RandomState;
+use std::fmt::Write as _;
+use std::hash::BuildHasher;
+use std::time::SystemTime;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::models::{ShareEmailOptions, SharedFile};
+use crate::scope::file_path;
+use crate::Error;
+
+/// Name messages are staged under.
+pub const FILE_NAME: &str = "message.eml";
+
+/// Length base64 lines are wrapped at, as MIME requires.
+const LINE_LEN: usize = 76;
+
+/// Longest line RFC 5322 allows, without its line break.
+const MAX_LINE_LEN: usize = 998;
+
+/// Bytes of UTF-8 text in one encoded word: 60 base64 characters, which
+/// keeps the word within the 75 characters RFC 2047 allows.
+const WORD_BYTES: usize = 45;
+
+/// Characters RFC 2231 parameter values may contain unencoded.
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// A file attached to a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub name: String,
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Attachment {
+    /// Reads `file`, named after its file name.
+    pub fn read(file: &SharedFile) -> crate::Result<Self> {
+        let path = file_path(&file.url);
+        let name = path.file_name().map_or_else(
+            || "attachment".into(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        Ok(Self {
+            name,
+            mime_type: file
+                .mime_type
+                .clone()
+                .unwrap_or_else(|| "application/octet-stream".into()),
+            data: std::fs::read(&path)?,
+        })
+    }
+}
+
+/// Rejects recipients that cannot be put into a header or passed to a mail
+/// client on its command line.
+pub fn check_recipients(options: &ShareEmailOptions) -> crate::Result<()> {
+    for recipient in options.to.iter().chain(&options.cc) {
+        let address = recipient.trim();
+        if !address.contains('@') || address.starts_with('-') || address.contains(char::is_control)
+        {
+            return Err(Error::InvalidInput(format!(
+                "invalid email address `{recipient}`"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Builds a MIME message that mail clients open as a draft, with the body as
+/// its text and `attachments` attached. `From` and `Date` are left to the
+/// client, which fills them in when the message is sent.
+pub fn build(options: &ShareEmailOptions, attachments: &[Attachment]) -> Vec<u8> {
+    let mut message = String::new();
+    header(&mut message, "MIME-Version", "1.0");
+    // Opens the message for editing in Outlook, Windows Mail and Apple Mail.
+    header(&mut message, "X-Unsent", "1");
+    if !options.to.is_empty() {
+        header(&mut message, "To", &recipients(&options.to));
+    }
+    if !options.cc.is_empty() {
+        header(&mut message, "Cc", &recipients(&options.cc));
+    }
+    if let Some(subject) = &options.subject {
+        header(&mut message, "Subject", &encode_text(subject));
+    }
+
+    let body = options.body.as_deref().unwrap_or_default();
+    if attachments.is_empty() {
+        text_part(&mut message, body);
+        return message.into_bytes();
+    }
+
+    let boundary = boundary();
+    header(
+        &mut message,
+        "Content-Type",
+        &format!("multipart/mixed; boundary=\"{boundary}\""),
+    );
+    message.push_str("\r\nThis is a multi-part message in MIME format.\r\n");
+    let _ = write!(message, "\r\n--{boundary}\r\n");
+    text_part(&mut message, body);
+    for attachment in attachments {
+        let _ = write!(message, "\r\n--{boundary}\r\n");
+        attachment_part(&mut message, attachment);
+    }
+    let _ = write!(message, "\r\n--{boundary}--\r\n");
+    message.into_bytes()
+}
+
+fn header(message: &mut String, name: &str, value: &str) {
+    let _ = write!(message, "{name}: {value}\r\n");
+}
+
+/// Comma-separated `recipients`, one per line, with display names encoded
+/// where they are not ASCII.
+fn recipients(recipients: &[String]) -> String {
+    recipients
+        .iter()
+        .map(|recipient| {
+            let recipient = recipient.trim();
+            match recipient.rsplit_once('<') {
+                Some((name, address)) if !name.trim().is_empty() => {
+                    let name = name.trim();
+                    let name = if name.is_ascii() {
+                        name.to_owned()
+                    } else {
+                        encode_text(name.trim_matches('"'))
+                    };
+                    format!("{name} <{address}")
+                }
+                _ => recipient.to_owned(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",\r\n ")
+}
+
+/// `text` as a header value: as is if it is ASCII, otherwise as RFC 2047
+/// encoded words on folded lines. Line breaks become spaces.
+fn encode_text(text: &str) -> String {
+    let text = text.replace(['\r', '\n'], " ");
+    if text.is_ascii() {
+        return text;
+    }
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in text.chars() {
+        if chunk.len() + c.len_utf8() > WORD_BYTES {
+            words.push(encoded_word(&chunk));
+            chunk.clear();
+        }
+        chunk.push(c);
+    }
+    words.push(encoded_word(&chunk));
+    words.join("\r\n ")
+}
+
+fn encoded_word(text: &str) -> String {
+    format!("=?UTF-8?B?{}?=", STANDARD.encode(text))
+}
+
+/// Separator of the message's parts. Base64 never contains `=_`, and the
+/// random part keeps text from containing it by chance.
+fn boundary() -> String {
+    let random = RandomState::new().hash_one(SystemTime::now());
+    format!("=_sharekit_{random:016x}")
+}
+
+/// The body as a text part: 7-bit if it is ASCII with short lines, base64
+/// otherwise. Line breaks become CRLF.
+fn text_part(message: &mut String, body: &str) {
+    let body = body.replace("\r\n", "\n").replace('\n', "\r\n");
+    header(message, "Content-Type", "text/plain; charset=utf-8");
+    if body.is_ascii() && body.lines().all(|line| line.len() <= MAX_LINE_LEN) {
+        header(message, "Content-Transfer-Encoding", "7bit");
+        message.push_str("\r\n");
+        message.push_str(&body);
+        message.push_str("\r\n");
+    } else {
+        header(message, "Content-Transfer-Encoding", "base64");
+        message.push_str("\r\n");
+        push_base64(message, body.as_bytes());
+    }
+}
+
+/// `attachment` as a base64 part. Names that are not ASCII are given as
+/// RFC 2231 `filename*`, and as an RFC 2047 `name` for clients that only
+/// read that.
+fn attachment_part(message: &mut String, attachment: &Attachment) {
+    let name = attachment.name.replace(char::is_control, "_");
+    let mime_type = if is_mime_type(&attachment.mime_type) {
+        attachment.mime_type.as_str()
+    } else {
+        "application/octet-stream"
+    };
+    if name.is_ascii() {
+        let quoted = name.replace('\\', "\\\\").replace('"', "\\\"");
+        header(
+            message,
+            "Content-Type",
+            &format!("{mime_type}; name=\"{quoted}\""),
+        );
+        header(
+            message,
+            "Content-Disposition",
+            &format!("attachment; filename=\"{quoted}\""),
+        );
+    } else {
+        header(
+            message,
+            "Content-Type",
+            &format!("{mime_type};\r\n name=\"{}\"", encode_text(&name)),
+        );
+        header(
+            message,
+            "Content-Disposition",
+            &format!(
+                "attachment;\r\n filename*=UTF-8''{}",
+                utf8_percent_encode(&name, ATTR_CHAR)
+            ),
+        );
+    }
+    header(message, "Content-Transfer-Encoding", "base64");
+    message.push_str("\r\n");
+    push_base64(message, &attachment.data);
+}
+
+fn push_base64(message: &mut String, data: &[u8]) {
+    let encoded = STANDARD.encode(data);
+    for line in encoded.as_bytes().chunks(LINE_LEN) {
+        // Base64 is ASCII, so every chunk is valid UTF-8.
+        message.push_str(std::str::from_utf8(line).unwrap_or_default());
+        message.push_str("\r\n");
+    }
+}
+
+/// Whether `mime_type` is a plain `type/subtype` that can be put into a
+/// header as is.
+fn is_mime_type(mime_type: &str) -> bool {
+    let is_token = |part: &str| {
+        !part.is_empty()
+            && part
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"!#$&-^_.+".contains(&b))
+    };
+    mime_type
+        .split_once('/')
+        .is_some_and(|(kind, subtype)| is_token(kind) && is_token(subtype))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(options: &ShareEmailOptions, attachments: &[Attachment]) -> String {
+        String::from_utf8(build(options, attachments)).expect("ASCII message")
+    }
+
+    fn decode_words(value: &str) -> String {
+        value
+            .split("\r\n ")
+            .map(|word| {
+                let encoded = word
+                    .strip_prefix("=?UTF-8?B?")
+                    .and_then(|word| word.strip_suffix("?="))
+                    .expect("encoded word");
+                String::from_utf8(STANDARD.decode(encoded).expect("base64")).expect("UTF-8")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plain_message_has_draft_headers_and_text() {
+        let options = ShareEmailOptions {
+            to: vec!["ana@example.com".into(), "Bo <bo@example.com>".into()],
+            cc: vec!["cy@example.com".into()],
+            subject: Some("Minutes".into()),
+            body: Some("Line one\nLine two".into()),
+        };
+        assert_eq!(
+            message(&options, &[]),
+            "MIME-Version: 1.0\r\n\
+             X-Unsent: 1\r\n\
+             To: ana@example.com,\r\n Bo <bo@example.com>\r\n\
+             Cc: cy@example.com\r\n\
+             Subject: Minutes\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\
+             Content-Transfer-Encoding: 7bit\r\n\
+             \r\n\
+             Line one\r\nLine two\r\n"
+        );
+    }
+
+    #[test]
+    fn non_ascii_subject_is_encoded_in_short_words() {
+        let subject = "Protokoll der Sitzung über die Änderungen – 日本語のテキスト";
+        let options = ShareEmailOptions {
+            subject: Some(subject.into()),
+            ..ShareEmailOptions::default()
+        };
+        let message = message(&options, &[]);
+        let value = message
+            .split_once("Subject: ")
+            .and_then(|(_, rest)| rest.split_once("\r\nContent-Type"))
+            .map(|(value, _)| value)
+            .expect("subject header");
+
+        assert!(value.split("\r\n ").count() > 1);
+        assert!(value.split("\r\n ").all(|word| word.len() <= 75));
+        assert_eq!(decode_words(value), subject);
+    }
+
+    #[test]
+    fn line_breaks_in_subjects_cannot_add_headers() {
+        let options = ShareEmailOptions {
+            subject: Some("Hi\r\nBcc: eve@example.com".into()),
+            ..ShareEmailOptions::default()
+        };
+        assert!(message(&options, &[]).contains("Subject: Hi  Bcc: eve@example.com\r\n"));
+    }
+
+    #[test]
+    fn non_ascii_display_names_are_encoded() {
+        let options = ShareEmailOptions {
+            to: vec!["\"Zoë Ångström\" <zoe@example.com>".into()],
+            ..ShareEmailOptions::default()
+        };
+        let message = message(&options, &[]);
+        let to = message
+            .lines()
+            .find_map(|line| line.strip_prefix("To: "))
+            .expect("to header");
+        let (name, address) = to.split_once(' ').expect("name and address");
+
+        assert_eq!(decode_words(name), "Zoë Ångström");
+        assert_eq!(address, "<zoe@example.com>");
+    }
+
+    #[test]
+    fn non_ascii_body_is_base64() {
+        let options = ShareEmailOptions {
+            body: Some("Grüße\nAlex".into()),
+            ..ShareEmailOptions::default()
+        };
+        let message = message(&options, &[]);
+        let (headers, body) = message.split_once("\r\n\r\n").expect("body");
+
+        assert!(headers.ends_with("Content-Transfer-Encoding: base64"));
+        assert_eq!(
+            STANDARD.decode(body.trim_end()).expect("base64"),
+            "Grüße\r\nAlex".as_bytes()
+        );
+    }
+
+    #[test]
+    fn attachments_are_base64_parts_between_boundaries() {
+        let attachment = Attachment {
+            name: "report \"final\".pdf".into(),
+            mime_type: "application/pdf".into(),
+            data: vec![7; 100],
+        };
+        let options = ShareEmailOptions {
+            body: Some("See attached".into()),
+            ..ShareEmailOptions::default()
+        };
+        let message = message(&options, &[attachment]);
+        let boundary = message
+            .split_once("boundary=\"")
+            .and_then(|(_, rest)| rest.split_once('"'))
+            .map(|(boundary, _)| boundary)
+            .expect("boundary");
+        let parts: Vec<_> = message.split(&format!("--{boundary}")).collect();
+
+        assert_eq!(parts.len(), 4);
+        assert!(parts[1].contains("\r\n\r\nSee attached\r\n"));
+        assert!(parts[2]
+            .contains("Content-Type: application/pdf; name=\"report \\\"final\\\".pdf\"\r\n"));
+        assert!(parts[2].contains(
+            "Content-Disposition: attachment; filename=\"report \\\"final\\\".pdf\"\r\n"
+        ));
+        let encoded = parts[2].split_once("\r\n\r\n").expect("content").1;
+        assert!(encoded.lines().all(|line| line.len() <= LINE_LEN));
+        assert_eq!(
+            STANDARD
+                .decode(encoded.replace("\r\n", ""))
+                .expect("base64"),
+            vec![7; 100]
+        );
+        assert_eq!(parts[3], "--\r\n");
+    }
+
+    #[test]
+    fn non_ascii_file_names_are_encoded() {
+        let attachment = Attachment {
+            name: "Résumé 履歴書.pdf".into(),
+            mime_type: "application/pdf".into(),
+            data: b"%PDF".to_vec(),
+        };
+        let message = message(&ShareEmailOptions::default(), &[attachment]);
+
+        assert!(message.contains(
+            "Content-Disposition: attachment;\r\n \
+             filename*=UTF-8''R%C3%A9sum%C3%A9%20%E5%B1%A5%E6%AD%B4%E6%9B%B8.pdf\r\n"
+        ));
+        let name = message
+            .split_once("name=\"")
+            .and_then(|(_, rest)| rest.split_once('"'))
+            .map(|(name, _)| name)
+            .expect("name parameter");
+        assert_eq!(decode_words(name), "Résumé 履歴書.pdf");
+    }
+
+    #[test]
+    fn invalid_mime_types_fall_back_to_octet_stream() {
+        let attachment = Attachment {
+            name: "a.bin".into(),
+            mime_type: "text/plain\r\nBcc: eve@example.com".into(),
+            data: Vec::new(),
+        };
+        let message = message(&ShareEmailOptions::default(), &[attachment]);
+        assert!(message.contains("Content-Type: application/octet-stream; name=\"a.bin\"\r\n"));
+        assert!(!message.contains("Bcc"));
+    }
+
+    #[test]
+    fn recipients_are_checked() {
+        let valid = ShareEmailOptions {
+            to: vec!["Ana <ana@example.com>".into()],
+            cc: vec!["bo@example.com".into()],
+            ..ShareEmailOptions::default()
+        };
+        assert!(check_recipients(&valid).is_ok());
+
+        for recipient in [
+            "ana",
+            "--help@example.com",
+            "ana@example.com\r\nBcc: eve@example.com",
+        ] {
+            let options = ShareEmailOptions {
+                cc: vec![recipient.into()],
+                ..ShareEmailOptions::default()
+            };
+            assert!(matches!(
+                check_recipients(&options),
+                Err(Error::InvalidInput(_))
+            ));
+        }
+    }
+}
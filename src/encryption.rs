@@ -0,0 +1,227 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipWriter};
+
+use crate::Error;
+
+/// How a shared payload is encrypted before it is staged, so the share only
+/// carries the encrypted file.
+///
+/// Keys never come from the webview: Rust code either sets
+/// [`ShareFileOptions::encryption`](crate::ShareFileOptions::encryption)
+/// directly, or registers an encryption with
+/// [`Builder::encryption`](crate::Builder::encryption) that the webview can
+/// then pick by name.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Encryption(Method);
+
+#[derive(Clone, PartialEq, Eq)]
+enum Method {
+    ZipAes { password: String },
+    Age { recipients: Vec<String> },
+}
+
+impl Encryption {
+    /// Packs the payload into a zip archive encrypted with AES-256, which
+    /// common archive tools open given `password`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::InvalidInput`] if `password` is empty.
+    pub fn zip_aes(password: impl Into<String>) -> crate::Result<Self> {
+        let password = password.into();
+        if password.is_empty() {
+            return Err(Error::InvalidInput("empty zip password".into()));
+        }
+        Ok(Self(Method::ZipAes { password }))
+    }
+
+    /// Encrypts the payload with [age](https://age-encryption.org) to
+    /// `recipients`, public keys starting with `age1`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::InvalidInput`] if there are no recipients or one
+    /// of them is not a valid key.
+    pub fn age<I>(recipients: I) -> crate::Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let recipients: Vec<String> = recipients.into_iter().map(Into::into).collect();
+        if recipients.is_empty() {
+            return Err(Error::InvalidInput("no age recipients".into()));
+        }
+        for recipient in &recipients {
+            age::x25519::Recipient::from_str(recipient).map_err(|e| {
+                Error::InvalidInput(format!("invalid age recipient `{recipient}`: {e}"))
+            })?;
+        }
+        Ok(Self(Method::Age { recipients }))
+    }
+
+    /// Password of a zip archive, which an archive of the payload can be
+    /// encrypted with directly instead of being wrapped in another one.
+    pub(crate) fn zip_password(&self) -> Option<&str> {
+        match &self.0 {
+            Method::ZipAes { password } => Some(password),
+            Method::Age { .. } => None,
+        }
+    }
+
+    /// Name of the encrypted file holding a file named `name`.
+    pub(crate) fn file_name(&self, name: &str) -> String {
+        match self.0 {
+            Method::ZipAes { .. } => format!("{name}.zip"),
+            Method::Age { .. } => format!("{name}.age"),
+        }
+    }
+
+    /// MIME type of the encrypted file.
+    pub(crate) const fn mime_type(&self) -> &'static str {
+        match self.0 {
+            Method::ZipAes { .. } => "application/zip",
+            Method::Age { .. } => "application/octet-stream",
+        }
+    }
+
+    /// Writes the encryption of a file named `name` to `out`. `write`
+    /// receives the writer for the plaintext; `len` is its size, if known.
+    pub(crate) fn encrypt<W: Write>(
+        &self,
+        out: W,
+        name: &str,
+        len: Option<u64>,
+        write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        match &self.0 {
+            Method::ZipAes { password } => {
+                let mut zip = ZipWriter::new_stream(out);
+                let options = SimpleFileOptions::default()
+                    .compression_method(CompressionMethod::Deflated)
+                    .large_file(len.is_none_or(|len| len > u64::from(u32::MAX)))
+                    .with_aes_encryption(AesMode::Aes256, password);
+                zip.start_file(name, options)?;
+                write(&mut zip)?;
+                zip.finish()?;
+            }
+            Method::Age { recipients } => {
+                let recipients = recipients
+                    .iter()
+                    .map(|recipient| age::x25519::Recipient::from_str(recipient))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(io::Error::other)?;
+                let mut writer = age::Encryptor::with_recipients(
+                    recipients.iter().map(|recipient| recipient as _),
+                )
+                .map_err(io::Error::other)?
+                .wrap_output(out)?;
+                write(&mut writer)?;
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Encryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Method::ZipAes { .. } => f
+                .debug_struct("ZipAes")
+                .field("password", &"<redacted>")
+                .finish(),
+            Method::Age { recipients } => f
+                .debug_struct("Age")
+                .field("recipients", recipients)
+                .finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use super::*;
+
+    fn encrypt(encryption: &Encryption, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        encryption
+            .encrypt(&mut out, "report.pdf", Some(data.len() as u64), |w| {
+                w.write_all(data)
+            })
+            .expect("encrypt");
+        out
+    }
+
+    #[test]
+    fn zip_aes_round_trips_with_the_password() {
+        let encryption = Encryption::zip_aes("correct horse").expect("password");
+        let encrypted = encrypt(&encryption, b"%PDF-1.7 patient record");
+
+        let mut zip = zip::ZipArchive::new(Cursor::new(encrypted)).expect("read zip");
+        let mut data = Vec::new();
+        zip.by_name_decrypt("report.pdf", b"correct horse")
+            .expect("decrypt")
+            .read_to_end(&mut data)
+            .expect("read entry");
+        assert_eq!(data, b"%PDF-1.7 patient record");
+
+        assert!(zip.by_name_decrypt("report.pdf", b"wrong").is_err());
+        assert!(zip.by_name("report.pdf").is_err());
+    }
+
+    #[test]
+    fn age_round_trips_with_the_identity() {
+        let identity = age::x25519::Identity::generate();
+        let other = age::x25519::Identity::generate();
+        let encryption = Encryption::age([identity.to_public().to_string()]).expect("recipients");
+        let encrypted = encrypt(&encryption, b"patient record");
+
+        assert!(!encrypted
+            .windows(b"patient".len())
+            .any(|window| window == b"patient"));
+        let decrypt = |identity: &age::x25519::Identity| {
+            let mut data = Vec::new();
+            age::Decryptor::new(encrypted.as_slice())
+                .expect("age header")
+                .decrypt(std::iter::once(identity as &dyn age::Identity))
+                .map_err(|e| e.to_string())?
+                .read_to_end(&mut data)
+                .map_err(|e| e.to_string())?;
+            Ok::<_, String>(data)
+        };
+        assert_eq!(decrypt(&identity).expect("decrypt"), b"patient record");
+        assert!(decrypt(&other).is_err());
+    }
+
+    #[test]
+    fn empty_zip_passwords_are_rejected() {
+        assert!(matches!(
+            Encryption::zip_aes(""),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_age_recipients_are_rejected() {
+        assert!(matches!(
+            Encryption::age(Vec::<String>::new()),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            Encryption::age(["ssh-ed25519 AAAA"]),
+            Err(Error::InvalidInput(message)) if message.contains("ssh-ed25519")
+        ));
+    }
+
+    #[test]
+    fn passwords_are_not_printed() {
+        let debug = format!("{:?}", Encryption::zip_aes("secret").expect("password"));
+        assert!(!debug.contains("secret"));
+    }
+}
@@ -1,4 +1,10 @@
-use serde::{ser::Serializer, Serialize};
+use serde::{
+    ser::{SerializeStruct, Serializer},
+    Serialize,
+};
+
+#[cfg(mobile)]
+use tauri::plugin::mobile::{ErrorResponse, PluginInvokeError};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -62,16 +68,173 @@ pub enum Error {
     WindowNotFound,
     #[error("Share cancelled")]
     ShareCancelled,
+    #[error("Path is not allowed by the share scope: {}", .0.display())]
+    PathNotAllowed(std::path::PathBuf),
+    #[error("Invalid share request: {0}")]
+    InvalidInput(String),
+    #[error("Invalid sharekit configuration: {0}")]
+    Config(String),
     #[cfg(target_os = "windows")]
     #[error("Windows API error: {0}")]
     WindowsApi(String),
+    #[cfg(target_os = "linux")]
+    #[error("Desktop portal error: {0}")]
+    Portal(String),
+    #[cfg(target_os = "linux")]
+    #[error("xdg-utils error: {0}")]
+    XdgUtils(String),
+}
+
+impl Error {
+    /// Stable, machine-readable identifier of the error kind, sent to the
+    /// webview next to the human-readable message.
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "IO",
+            Self::PluginInvoke(_) => "PLATFORM",
+            Self::UnsupportedPlatform => "UNSUPPORTED_PLATFORM",
+            Self::WindowNotFound => "WINDOW_NOT_FOUND",
+            Self::ShareCancelled => "CANCELLED",
+            Self::PathNotAllowed(_) => "PATH_NOT_ALLOWED",
+            Self::InvalidInput(_) => "INVALID_INPUT",
+            Self::Config(_) => "CONFIG",
+            #[cfg(target_os = "windows")]
+            Self::WindowsApi(_) => "PLATFORM",
+            #[cfg(target_os = "linux")]
+            Self::Portal(_) | Self::XdgUtils(_) => "PLATFORM",
+        }
+    }
 }
 
+/// Serialized as `{ "code": "...", "message": "..." }`.
 impl Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        let mut state = serializer.serialize_struct("Error", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Message the native plugins used for cancellations before they sent a code.
+const CANCELLED_MESSAGE: &str = "Share cancelled";
+
+/// Turns a native plugin failure into the `Error` variant the desktop
+/// backends would have produced for the same situation.
+#[cfg_attr(not(mobile), allow(dead_code))]
+pub fn map_invoke_error(err: PluginInvokeError) -> Error {
+    match err {
+        PluginInvokeError::InvokeRejected(response) => map_error_response(response),
+        other => other.into(),
+    }
+}
+
+#[cfg_attr(not(mobile), allow(dead_code))]
+fn map_error_response(response: ErrorResponse) -> Error {
+    match (response.code.as_deref(), response.message.as_deref()) {
+        (Some("CANCELLED"), _) | (None, Some(CANCELLED_MESSAGE)) => Error::ShareCancelled,
+        (Some("UNSUPPORTED_PLATFORM"), _) => Error::UnsupportedPlatform,
+        _ => PluginInvokeError::InvokeRejected(response).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(code: Option<&str>, message: Option<&str>) -> ErrorResponse {
+        ErrorResponse {
+            code: code.map(Into::into),
+            message: message.map(Into::into),
+            data: (),
+        }
+    }
+
+    fn rejected(code: Option<&str>, message: Option<&str>) -> Error {
+        map_invoke_error(PluginInvokeError::InvokeRejected(response(code, message)))
+    }
+
+    #[test]
+    fn serializes_code_and_message() {
+        assert_eq!(
+            serde_json::to_value(Error::ShareCancelled).expect("serialize"),
+            serde_json::json!({ "code": "CANCELLED", "message": "Share cancelled" })
+        );
+        assert_eq!(
+            serde_json::to_value(Error::UnsupportedPlatform).expect("serialize"),
+            serde_json::json!({
+                "code": "UNSUPPORTED_PLATFORM",
+                "message": "This feature is not supported on this platform",
+            })
+        );
+    }
+
+    #[test]
+    fn io_errors_keep_their_message() {
+        let err = Error::from(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "missing.pdf",
+        ));
+        assert_eq!(
+            serde_json::to_value(err).expect("serialize"),
+            serde_json::json!({ "code": "IO", "message": "missing.pdf" })
+        );
+    }
+
+    #[test]
+    fn cancelled_code_maps_to_share_cancelled() {
+        assert!(matches!(
+            rejected(Some("CANCELLED"), Some("Share cancelled")),
+            Error::ShareCancelled
+        ));
+        assert!(matches!(
+            rejected(Some("CANCELLED"), None),
+            Error::ShareCancelled
+        ));
+    }
+
+    #[test]
+    fn cancelled_message_without_code_maps_to_share_cancelled() {
+        assert!(matches!(
+            rejected(None, Some("Share cancelled")),
+            Error::ShareCancelled
+        ));
+    }
+
+    #[test]
+    fn unsupported_code_maps_to_unsupported_platform() {
+        assert!(matches!(
+            rejected(Some("UNSUPPORTED_PLATFORM"), Some("No activity")),
+            Error::UnsupportedPlatform
+        ));
+    }
+
+    #[test]
+    fn other_rejections_stay_plugin_errors() {
+        let err = rejected(None, Some("Invalid file URL"));
+        assert!(matches!(
+            &err,
+            Error::PluginInvoke(PluginInvokeError::InvokeRejected(r))
+                if r.message.as_deref() == Some("Invalid file URL")
+        ));
+        assert_eq!(err.code(), "PLATFORM");
+
+        assert!(matches!(
+            rejected(Some("OTHER"), Some("Share cancelled")),
+            Error::PluginInvoke(_)
+        ));
+    }
+
+    #[test]
+    fn non_rejections_stay_plugin_errors() {
+        let json = serde_json::from_str::<u8>("x").expect_err("invalid JSON");
+        assert!(matches!(
+            map_invoke_error(PluginInvokeError::CannotDeserializeResponse(json)),
+            Error::PluginInvoke(PluginInvokeError::CannotDeserializeResponse(_))
+        ));
     }
 }
@@ -0,0 +1,59 @@
+use crate::Error;
+
+/// Width plain text is rendered at. Wide enough for paragraphs to stay on
+/// one line, leaving wrapping to the app the text is shared with.
+const TEXT_WIDTH: usize = 4096;
+
+/// `html` with only formatting, links and images left: scripts, styles,
+/// event handlers and `javascript:` links are removed.
+pub fn sanitize(html: &str) -> String {
+    ammonia::clean(html)
+}
+
+/// Plain-text alternative of `html`, with links listed as footnotes.
+pub fn to_plain_text(html: &str) -> crate::Result<String> {
+    let text = html2text::config::plain()
+        .allow_width_overflow()
+        .string_from_read(html.as_bytes(), TEXT_WIDTH)
+        .map_err(|e| Error::InvalidInput(format!("invalid HTML: {e}")))?;
+    Ok(text
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_and_handlers_are_removed() {
+        let html = sanitize(
+            r#"<p onclick="steal()">Hi <b>there</b><script>steal()</script></p><a href="javascript:steal()">x</a>"#,
+        );
+        assert_eq!(
+            html,
+            "<p>Hi <b>there</b></p><a rel=\"noopener noreferrer\">x</a>"
+        );
+    }
+
+    #[test]
+    fn plain_text_keeps_paragraphs_and_links() {
+        let text = to_plain_text(
+            r#"<h1>Notes</h1><p>Buy <b>milk</b>.</p><p>See <a href="https://example.com/list">the list</a>.</p>"#,
+        )
+        .expect("text");
+        assert_eq!(
+            text,
+            "# Notes\n\nBuy **milk**.\n\nSee [the list][1].\n\n[1]: https://example.com/list"
+        );
+    }
+
+    #[test]
+    fn long_paragraphs_are_not_wrapped() {
+        let sentence = "word ".repeat(100);
+        let text = to_plain_text(&format!("<p>{sentence}</p>")).expect("text");
+        assert_eq!(text, sentence.trim_end());
+    }
+}
@@ -0,0 +1,536 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageReader, Rgb, RgbImage, RgbaImage};
+use resvg::{tiny_skia, usvg};
+
+use crate::models::{ImageFormat, ImageOptions};
+use crate::staging::split_extension;
+use crate::{mime, Error};
+
+const DEFAULT_QUALITY: u8 = 85;
+
+/// Bytes read to tell whether a file is an image.
+const SNIFF_LEN: u64 = 512;
+
+/// Largest `maxDimension`, the longest side an SVG image can be rendered at.
+const MAX_DIMENSION: u32 = u16::MAX as u32;
+
+/// Most pixels an SVG image is rendered to: 64 megapixels, 256 MiB of RGBA.
+/// The renderer aborts the app when it cannot allocate the pixels, so
+/// larger images are rejected up front.
+const MAX_PIXELS: u64 = 64 * 1024 * 1024;
+
+/// An image re-encoded for sharing.
+#[derive(Debug)]
+pub struct Processed {
+    pub data: Vec<u8>,
+    pub format: ImageFormat,
+}
+
+impl Processed {
+    /// `name` with the extension of the new format.
+    pub fn file_name(&self, name: &str) -> String {
+        let (stem, _) = split_extension(name);
+        format!("{stem}.{}", extension(self.format))
+    }
+}
+
+pub const fn extension(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::Png => "png",
+        ImageFormat::Webp => "webp",
+    }
+}
+
+pub const fn mime_type(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Jpeg => "image/jpeg",
+        ImageFormat::Png => "image/png",
+        ImageFormat::Webp => "image/webp",
+    }
+}
+
+/// Rejects options no image can be processed with.
+pub fn check(options: &ImageOptions) -> crate::Result<()> {
+    if options.max_dimension == Some(0) {
+        return Err(Error::InvalidInput(
+            "maxDimension must be at least 1".into(),
+        ));
+    }
+    if options.max_dimension > Some(MAX_DIMENSION) {
+        return Err(Error::InvalidInput(format!(
+            "maxDimension must be at most {MAX_DIMENSION}"
+        )));
+    }
+    if options
+        .quality
+        .is_some_and(|quality| !(1..=100).contains(&quality))
+    {
+        return Err(Error::InvalidInput(
+            "quality must be between 1 and 100".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Images that can be processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Raster(image::ImageFormat),
+    Svg,
+}
+
+fn source(data: &[u8], name: &Path) -> Option<Source> {
+    match image::guess_format(data) {
+        Ok(
+            format @ (image::ImageFormat::Jpeg
+            | image::ImageFormat::Png
+            | image::ImageFormat::WebP
+            | image::ImageFormat::Gif),
+        ) => Some(Source::Raster(format)),
+        _ => (mime::infer(data, name, &BTreeMap::new()).as_deref() == Some("image/svg+xml"))
+            .then_some(Source::Svg),
+    }
+}
+
+/// Processes the image at `path` like [`process`]. Only the first bytes of
+/// files that are not images are read.
+pub fn process_file(path: &Path, options: &ImageOptions) -> io::Result<Option<Processed>> {
+    let mut file = File::open(path)?;
+    let mut data = Vec::new();
+    (&mut file).take(SNIFF_LEN).read_to_end(&mut data)?;
+    if source(&data, path).is_none() {
+        return Ok(None);
+    }
+    file.read_to_end(&mut data)?;
+    process(&data, path, options)
+}
+
+/// Re-encodes the image in `data`, a file named `name`, as `options` ask.
+/// Returns `None` if `data` is not a JPEG, PNG, WebP, GIF or SVG image, or
+/// already matches the options. Orientation is applied to the pixels, since
+/// the re-encoded image has no metadata.
+///
+/// This decodes and encodes the whole image, so call it off the async
+/// runtime.
+pub fn process(data: &[u8], name: &Path, options: &ImageOptions) -> io::Result<Option<Processed>> {
+    match source(data, name) {
+        Some(Source::Raster(format)) => process_raster(data, format, options),
+        Some(Source::Svg) => {
+            let image = rasterise(data, options.max_dimension)?;
+            let format = options.format.unwrap_or(ImageFormat::Png);
+            encode(&image, format, options.quality).map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
+fn process_raster(
+    data: &[u8],
+    format: image::ImageFormat,
+    options: &ImageOptions,
+) -> io::Result<Option<Processed>> {
+    let own_format = match format {
+        image::ImageFormat::Jpeg => Some(ImageFormat::Jpeg),
+        image::ImageFormat::Png => Some(ImageFormat::Png),
+        image::ImageFormat::WebP => Some(ImageFormat::Webp),
+        _ => None,
+    };
+    let target = options.format.or(own_format).unwrap_or(ImageFormat::Png);
+
+    let decoder = decoder(data, format)?;
+    let (width, height) = decoder.dimensions();
+    let too_large = options
+        .max_dimension
+        .is_some_and(|max| width.max(height) > max);
+    let requality = options.quality.is_some() && target != ImageFormat::Png;
+    if Some(target) == own_format && !too_large && !requality {
+        return Ok(None);
+    }
+
+    let mut image = decode(decoder)?;
+    if let Some(max) = options.max_dimension.filter(|_| too_large) {
+        image = image.resize(max, max, FilterType::Lanczos3);
+    }
+    encode(&image, target, options.quality).map(Some)
+}
+
+/// Decodes a JPEG, PNG, WebP or GIF image to RGBA pixels, with its
+/// orientation applied.
+pub fn decode_rgba(data: &[u8]) -> io::Result<RgbaImage> {
+    let Some(Source::Raster(format)) = source(data, Path::new("")) else {
+        return Err(invalid("not a JPEG, PNG, WebP or GIF image"));
+    };
+    Ok(decode(decoder(data, format)?)?.into_rgba8())
+}
+
+/// Encodes RGBA pixels, `width` pixels per row, as `format`.
+pub fn encode_rgba(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    format: ImageFormat,
+    quality: Option<u8>,
+) -> io::Result<Processed> {
+    let image = RgbaImage::from_raw(width, height, rgba.to_vec())
+        .ok_or_else(|| invalid("too few pixels for the image size"))?;
+    encode(&DynamicImage::ImageRgba8(image), format, quality)
+}
+
+fn decoder(data: &[u8], format: image::ImageFormat) -> io::Result<impl ImageDecoder + '_> {
+    let mut reader = ImageReader::new(Cursor::new(data));
+    reader.set_format(format);
+    reader.into_decoder().map_err(invalid)
+}
+
+fn decode(mut decoder: impl ImageDecoder) -> io::Result<DynamicImage> {
+    let orientation = decoder.orientation().map_err(invalid)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(invalid)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Fonts for SVG text, loaded from the system once.
+fn fonts() -> Arc<usvg::fontdb::Database> {
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut fonts = usvg::fontdb::Database::new();
+            fonts.load_system_fonts();
+            Arc::new(fonts)
+        })
+        .clone()
+}
+
+/// Renders an SVG image at its own size, or scaled so its larger side is
+/// `size` pixels. Fails with [`io::ErrorKind::InvalidInput`] if the image
+/// would have more than [`MAX_PIXELS`] pixels.
+fn rasterise(data: &[u8], size: Option<u32>) -> io::Result<DynamicImage> {
+    let mut options = usvg::Options {
+        fontdb: fonts(),
+        ..usvg::Options::default()
+    };
+    // Only embedded images are drawn: the SVG may come from the webview and
+    // must not read files outside the share scope.
+    options.image_href_resolver.resolve_string = Box::new(|_, _| None);
+    let tree = usvg::Tree::from_data(data, &options).map_err(invalid)?;
+
+    let natural = tree.size();
+    let too_large = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "the SVG image is larger than {} megapixels",
+                MAX_PIXELS >> 20
+            ),
+        )
+    };
+    let scale = match size {
+        Some(size) => {
+            let size = u16::try_from(size).map_err(|_| too_large())?;
+            f32::from(size) / natural.width().max(natural.height())
+        }
+        None => 1.0,
+    };
+    // The range check makes the cast lossless.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let pixels = |length: f32| {
+        let length = (length * scale).ceil();
+        (1.0..=f32::from(u16::MAX))
+            .contains(&length)
+            .then_some(length as u32)
+    };
+    let width = pixels(natural.width()).ok_or_else(too_large)?;
+    let height = pixels(natural.height()).ok_or_else(too_large)?;
+    if u64::from(width) * u64::from(height) > MAX_PIXELS {
+        return Err(too_large());
+    }
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).ok_or_else(too_large)?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    let (width, height) = (pixmap.width(), pixmap.height());
+    let rgba = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let pixel = pixel.demultiply();
+            [pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()]
+        })
+        .collect();
+    image::RgbaImage::from_raw(width, height, rgba)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(too_large)
+}
+
+fn encode(image: &DynamicImage, format: ImageFormat, quality: Option<u8>) -> io::Result<Processed> {
+    let quality = quality.unwrap_or(DEFAULT_QUALITY);
+    let mut data = Vec::new();
+    match format {
+        ImageFormat::Jpeg => JpegEncoder::new_with_quality(&mut data, quality)
+            .encode_image(&flatten(image))
+            .map_err(invalid)?,
+        ImageFormat::Png => image
+            .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+            .map_err(invalid)?,
+        ImageFormat::Webp => {
+            let (width, height) = (image.width(), image.height());
+            let encoded = if image.color().has_alpha() {
+                let rgba = image.to_rgba8();
+                webp::Encoder::from_rgba(&rgba, width, height).encode(f32::from(quality))
+            } else {
+                let rgb = image.to_rgb8();
+                webp::Encoder::from_rgb(&rgb, width, height).encode(f32::from(quality))
+            };
+            data.extend_from_slice(&encoded);
+        }
+    }
+    Ok(Processed { data, format })
+}
+
+/// `image` without transparency, composited over white, for JPEG.
+fn flatten(image: &DynamicImage) -> RgbImage {
+    if !image.color().has_alpha() {
+        return image.to_rgb8();
+    }
+    let rgba = image.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [red, green, blue, alpha] = rgba.get_pixel(x, y).0;
+        let over_white = |channel: u8| {
+            u8::try_from(
+                (u16::from(channel) * u16::from(alpha) + 255 * u16::from(255 - alpha)) / 255,
+            )
+            .unwrap_or(u8::MAX)
+        };
+        Rgb([over_white(red), over_white(green), over_white(blue)])
+    })
+}
+
+fn invalid(e: impl Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GenericImageView, Rgba};
+
+    use super::*;
+
+    fn options(
+        max_dimension: Option<u32>,
+        quality: Option<u8>,
+        format: Option<ImageFormat>,
+    ) -> ImageOptions {
+        ImageOptions {
+            max_dimension,
+            quality,
+            format,
+        }
+    }
+
+    fn encoded(image: &DynamicImage, format: image::ImageFormat) -> Vec<u8> {
+        let mut data = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut data), format)
+            .expect("encode");
+        data
+    }
+
+    /// Half transparent, half opaque red.
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbaImage::from_fn(width, height, |x, _| {
+            if x < width / 2 {
+                Rgba([0, 0, 0, 0])
+            } else {
+                Rgba([255, 0, 0, 255])
+            }
+        });
+        encoded(&DynamicImage::ImageRgba8(image), image::ImageFormat::Png)
+    }
+
+    fn decoded(processed: &Processed) -> DynamicImage {
+        image::load_from_memory(&processed.data).expect("decode")
+    }
+
+    #[test]
+    fn large_images_are_scaled_down_keeping_their_aspect_ratio() {
+        let processed = process(
+            &png(400, 200),
+            Path::new("chart.png"),
+            &options(Some(100), None, None),
+        )
+        .expect("process")
+        .expect("resized");
+
+        assert_eq!(processed.format, ImageFormat::Png);
+        assert_eq!(decoded(&processed).dimensions(), (100, 50));
+    }
+
+    #[test]
+    fn matching_images_are_left_alone() {
+        let png = png(40, 20);
+        for options in [
+            options(Some(40), None, None),
+            options(None, None, Some(ImageFormat::Png)),
+            options(None, Some(60), None),
+        ] {
+            assert!(process(&png, Path::new("a.png"), &options)
+                .expect("process")
+                .is_none());
+        }
+        assert!(
+            process(b"text", Path::new("a.txt"), &options(Some(1), None, None))
+                .expect("process")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn png_to_jpeg_flattens_transparency_onto_white() {
+        let processed = process(
+            &png(40, 20),
+            Path::new("logo.png"),
+            &options(None, None, Some(ImageFormat::Jpeg)),
+        )
+        .expect("process")
+        .expect("converted");
+
+        assert_eq!(processed.file_name("logo.png"), "logo.jpg");
+        assert_eq!(
+            image::guess_format(&processed.data).expect("format"),
+            image::ImageFormat::Jpeg
+        );
+        let image = decoded(&processed).to_rgb8();
+        assert!(image.get_pixel(2, 10).0.iter().all(|&c| c > 240));
+        let [red, green, blue] = image.get_pixel(37, 10).0;
+        assert!(red > 240 && green < 20 && blue < 20);
+    }
+
+    #[test]
+    fn webp_converts_to_jpeg() {
+        let webp = encoded(
+            &DynamicImage::ImageRgb8(RgbImage::from_pixel(30, 30, Rgb([0, 128, 255]))),
+            image::ImageFormat::WebP,
+        );
+        let processed = process(
+            &webp,
+            Path::new("photo.webp"),
+            &options(None, None, Some(ImageFormat::Jpeg)),
+        )
+        .expect("process")
+        .expect("converted");
+        assert_eq!(processed.format, ImageFormat::Jpeg);
+        assert_eq!(decoded(&processed).dimensions(), (30, 30));
+    }
+
+    #[test]
+    fn lower_quality_gives_smaller_files() {
+        let noise = RgbImage::from_fn(64, 64, |x, y| {
+            let v = ((x * 7919 + y * 104_729) % 251) as u8;
+            Rgb([v, v.wrapping_mul(3), v.wrapping_mul(7)])
+        });
+        let jpeg = encoded(&DynamicImage::ImageRgb8(noise), image::ImageFormat::Png);
+        let size = |quality, format| {
+            process(
+                &jpeg,
+                Path::new("noise.png"),
+                &options(None, Some(quality), Some(format)),
+            )
+            .expect("process")
+            .expect("encoded")
+            .data
+            .len()
+        };
+        assert!(size(20, ImageFormat::Jpeg) < size(95, ImageFormat::Jpeg));
+        assert!(size(20, ImageFormat::Webp) < size(95, ImageFormat::Webp));
+    }
+
+    const SVG: &[u8] = br##"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20">
+        <rect width="20" height="20" fill="#00f"/>
+        <image href="/etc/hosts" width="20" height="20"/>
+    </svg>"##;
+
+    #[test]
+    fn svg_images_are_rasterised_to_png() {
+        let processed = process(SVG, Path::new("chart.svg"), &ImageOptions::default())
+            .expect("process")
+            .expect("rasterised");
+        assert_eq!(processed.format, ImageFormat::Png);
+        assert_eq!(processed.file_name("chart.svg"), "chart.png");
+        let image = decoded(&processed).to_rgba8();
+        assert_eq!(image.dimensions(), (40, 20));
+        assert_eq!(image.get_pixel(5, 5).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(30, 5).0[3], 0);
+
+        let scaled = process(SVG, Path::new("chart.svg"), &options(Some(400), None, None))
+            .expect("process")
+            .expect("rasterised");
+        assert_eq!(decoded(&scaled).dimensions(), (400, 200));
+    }
+
+    #[test]
+    fn huge_svg_images_are_rejected() {
+        let huge = br#"<svg xmlns="http://www.w3.org/2000/svg" width="60000" height="60000"/>"#;
+        let err =
+            process(huge, Path::new("huge.svg"), &ImageOptions::default()).expect_err("too large");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let err = process(
+            SVG,
+            Path::new("chart.svg"),
+            &options(Some(40_000), None, None),
+        )
+        .expect_err("too large");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn rgba_pixels_are_encoded_in_the_requested_format() {
+        let rgba: Vec<u8> = [255, 0, 0, 255].repeat(6);
+        for format in [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::Webp] {
+            let processed = encode_rgba(&rgba, 3, 2, format, None).expect("encode");
+            assert_eq!(processed.format, format);
+            assert_eq!(decoded(&processed).dimensions(), (3, 2));
+        }
+        assert!(encode_rgba(&rgba[..20], 3, 2, ImageFormat::Png, None).is_err());
+    }
+
+    #[test]
+    fn encoded_images_decode_to_rgba() {
+        let image = decode_rgba(&png(4, 2)).expect("decode");
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(image.get_pixel(3, 0).0, [255, 0, 0, 255]);
+        assert_eq!(
+            decode_rgba(b"<svg/>").expect_err("svg").kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        assert!(check(&options(Some(1), Some(1), None)).is_ok());
+        assert!(matches!(
+            check(&options(Some(0), None, None)),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            check(&options(Some(65_536), None, None)),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            check(&options(None, Some(101), None)),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
@@ -1,12 +1,23 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use tauri::{
-    plugin::{Builder, TauriPlugin},
-    Manager, Runtime,
+    image::Image,
+    plugin::{self, TauriPlugin},
+    Manager, Runtime, WebviewWindow,
 };
 
 pub use models::*;
 
-#[cfg(all(desktop, not(target_os = "macos"), not(target_os = "windows")))]
+#[cfg(all(
+    desktop,
+    not(target_os = "macos"),
+    not(target_os = "windows"),
+    not(target_os = "linux")
+))]
 mod desktop;
+#[cfg(target_os = "linux")]
+mod linux;
 #[cfg(target_os = "macos")]
 mod macos;
 #[cfg(mobile)]
@@ -14,24 +25,566 @@ mod mobile;
 #[cfg(target_os = "windows")]
 mod windows;
 
+mod archive;
+mod backend;
 mod commands;
+mod config;
+mod data;
+mod eml;
+mod encryption;
 mod error;
+mod html;
+mod imaging;
+mod link;
+mod metadata;
+mod mime;
+#[cfg(feature = "mock")]
+pub mod mock;
 mod models;
+mod request;
+mod scope;
+mod staging;
+#[cfg(test)]
+mod test_util;
+mod web_share;
 
+pub use backend::{ShareBackend, ShareFuture};
+pub use config::{Config, Fallback, LinuxBackend, LinuxConfig, ScopeConfig};
+pub use encryption::Encryption;
 pub use error::{Error, Result};
+pub use request::{ShareParent, ShareRequest};
+pub use staging::Staging;
 
-#[cfg(all(desktop, not(target_os = "macos"), not(target_os = "windows")))]
-use desktop::ShareKit;
+#[cfg(all(
+    desktop,
+    not(target_os = "macos"),
+    not(target_os = "windows"),
+    not(target_os = "linux")
+))]
+use desktop::NativeBackend;
+#[cfg(target_os = "linux")]
+use linux::NativeBackend;
 #[cfg(target_os = "macos")]
-use macos::ShareKit;
+use macos::NativeBackend;
 #[cfg(mobile)]
-use mobile::ShareKit;
+use mobile::NativeBackend;
 #[cfg(target_os = "windows")]
-use windows::ShareKit;
+use windows::NativeBackend;
+
+/// Access to the share APIs.
+///
+/// Fills in defaults from the plugin [`Config`] and forwards every call to
+/// the registered [`ShareBackend`].
+pub struct ShareKit<R: Runtime> {
+    backend: Box<dyn ShareBackend<R>>,
+    config: Config,
+    staging: Staging,
+    encryptions: BTreeMap<String, Encryption>,
+}
+
+impl<R: Runtime> ShareKit<R> {
+    /// Settings from `plugins.sharekit`.
+    #[must_use]
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Temporary copies of shared content, e.g. to delete them once the app
+    /// knows they are no longer needed.
+    #[must_use]
+    pub const fn staging(&self) -> &Staging {
+        &self.staging
+    }
+
+    /// Encryption registered under `name` with [`Builder::encryption`].
+    #[must_use]
+    pub fn encryption(&self, name: &str) -> Option<&Encryption> {
+        self.encryptions.get(name)
+    }
+
+    /// What the share backend supports, e.g. to hide a share button on
+    /// platforms that cannot share.
+    #[must_use]
+    pub fn capabilities(&self) -> ShareCapabilities {
+        self.backend.capabilities()
+    }
+
+    /// Whether the share backend can share `items`, without showing any UI.
+    /// Links with local URLs, such as `file:` URLs, cannot be shared.
+    #[must_use]
+    pub fn can_share(&self, items: &[ShareItem]) -> bool {
+        let links_valid = items.iter().all(|item| match item {
+            ShareItem::Url { url } => link::normalize(url).is_ok(),
+            _ => true,
+        });
+        links_valid && self.capabilities().supports(items)
+    }
+
+    pub async fn share_text(
+        &self,
+        window: WebviewWindow<R>,
+        text: String,
+        mut options: ShareTextOptions,
+    ) -> crate::Result<()> {
+        options.title = self.config.title(options.title);
+        self.backend.share_text(window, text, options).await
+    }
+
+    /// Shares formatted text. The HTML is sanitized first, and `text` is
+    /// generated from it if it is `None`, for apps that cannot show HTML.
+    pub async fn share_html(
+        &self,
+        window: WebviewWindow<R>,
+        html: &str,
+        text: Option<String>,
+        mut options: ShareHtmlOptions,
+    ) -> crate::Result<()> {
+        let html = html::sanitize(html);
+        let text = match text {
+            Some(text) => text,
+            None => html::to_plain_text(&html)?,
+        };
+        options.title = self.config.title(options.title);
+        self.backend.share_html(window, html, text, options).await
+    }
+
+    /// Shares a link, normalized first. Local URLs, such as `file:` and
+    /// `blob:` URLs, cannot be shared.
+    pub async fn share_url(
+        &self,
+        window: WebviewWindow<R>,
+        url: &str,
+        mut options: ShareUrlOptions,
+    ) -> crate::Result<()> {
+        let url = link::normalize(url)?;
+        options.title = self.config.title(options.title);
+        if let Some(thumbnail) = &mut options.thumbnail {
+            to_file_path(thumbnail);
+        }
+        self.backend.share_url(window, url, options).await
+    }
+
+    /// Shares a single file. With a `file_name`, a staged copy of the file
+    /// with that name is shared instead, and with `image` options, a resized
+    /// or converted copy of an image.
+    ///
+    /// Directories, and files shared with `archive` options, are packed into
+    /// a staged archive first. A `file_name` then names the archive unless
+    /// the archive options name it. With `strip_metadata`, the copy or the
+    /// archived files have their privacy metadata removed. With an
+    /// `encryption`, only an encrypted copy of the file or archive is staged
+    /// and shared.
+    pub async fn share_file(
+        &self,
+        window: WebviewWindow<R>,
+        mut url: String,
+        mut options: ShareFileOptions,
+    ) -> crate::Result<()> {
+        options.title = self.config.title(options.title);
+        to_file_path(&mut url);
+        let paths = vec![scope::file_path(&url)];
+        let strip = self.config.strip_metadata(options.strip_metadata.take());
+        let encryption = options.encryption.take();
+        let image = options.image.take();
+        if let Some(image) = &image {
+            imaging::check(image)?;
+        }
+        if archive::is_needed(&paths, options.archive.as_ref()) {
+            let mut archive = options.archive.take().unwrap_or_default();
+            let file_name = options.file_name.take();
+            archive.name = archive.name.or(file_name);
+            let encryption = encryption.clone();
+            let path = self
+                .staging_task(move |staging| {
+                    staging.stage_archive(&paths, &archive, strip, encryption.as_ref())
+                })
+                .await?;
+            url = path.to_string_lossy().into_owned();
+        } else if strip || image.is_some() || encryption.is_some() || options.file_name.is_some() {
+            let name = options.file_name.take();
+            let encryption = encryption.clone();
+            let staged = self
+                .staging_task(move |staging| {
+                    staging.stage_file(
+                        &paths[0],
+                        name.as_deref(),
+                        image.as_ref(),
+                        strip,
+                        encryption.as_ref(),
+                    )
+                })
+                .await?;
+            if let Some(path) = staged {
+                url = path.to_string_lossy().into_owned();
+                // A converted image has a new type, inferred below.
+                if image.is_some() {
+                    options.mime_type = None;
+                }
+            }
+        }
+        if let Some(encryption) = encryption {
+            options.mime_type = Some(encryption.mime_type().into());
+        }
+        self.infer_mime_types(vec![(&url, &mut options.mime_type)])
+            .await?;
+        self.backend.share_file(window, url, options).await
+    }
+
+    /// Shares several files at once. Groups shared with `archive` options, or
+    /// containing a directory, are packed into one staged archive which is
+    /// shared like [`share_file`](Self::share_file). With `strip_metadata`,
+    /// files are shared as copies without privacy metadata.
+    pub async fn share_files(
+        &self,
+        window: WebviewWindow<R>,
+        mut files: Vec<SharedFile>,
+        mut options: ShareFilesOptions,
+    ) -> crate::Result<()> {
+        if files.is_empty() {
+            return Err(Error::InvalidInput("files must not be empty".into()));
+        }
+        for file in &mut files {
+            to_file_path(&mut file.url);
+        }
+        let paths: Vec<_> = files
+            .iter()
+            .map(|file| scope::file_path(&file.url))
+            .collect();
+        let strip = self.config.strip_metadata(options.strip_metadata.take());
+        if archive::is_needed(&paths, options.archive.as_ref()) {
+            let archive = options.archive.take().unwrap_or_default();
+            let path = self
+                .staging_task(move |staging| staging.stage_archive(&paths, &archive, strip, None))
+                .await?;
+            return self
+                .share_file(
+                    window,
+                    path.to_string_lossy().into_owned(),
+                    ShareFileOptions {
+                        title: options.title,
+                        position: options.position,
+                        strip_metadata: Some(false),
+                        ..ShareFileOptions::default()
+                    },
+                )
+                .await;
+        }
+
+        if strip {
+            let stripped = self
+                .staging_task(move |staging| {
+                    staging.stage_stripped(paths.iter().map(PathBuf::as_path))
+                })
+                .await?;
+            for (file, path) in files.iter_mut().zip(stripped) {
+                if let Some(path) = path {
+                    file.url = path.to_string_lossy().into_owned();
+                }
+            }
+        }
+        options.title = self.config.title(options.title);
+        self.infer_mime_types(
+            files
+                .iter_mut()
+                .map(|file| (file.url.as_str(), &mut file.mime_type))
+                .collect(),
+        )
+        .await?;
+        self.backend.share_files(window, files, options).await
+    }
+
+    /// Shares files by email, in a new email addressed to the recipients
+    /// where the platform can address one.
+    pub async fn share_email(
+        &self,
+        window: WebviewWindow<R>,
+        mut files: Vec<SharedFile>,
+        options: ShareEmailOptions,
+    ) -> crate::Result<()> {
+        eml::check_recipients(&options)?;
+        for file in &mut files {
+            to_file_path(&mut file.url);
+        }
+        if let Some(file) = files
+            .iter()
+            .find(|file| scope::file_path(&file.url).is_dir())
+        {
+            return Err(Error::InvalidInput(format!(
+                "`{}` is a directory and cannot be attached",
+                file.url
+            )));
+        }
+        self.infer_mime_types(
+            files
+                .iter_mut()
+                .map(|file| (file.url.as_str(), &mut file.mime_type))
+                .collect(),
+        )
+        .await?;
+        self.backend.share_email(window, files, options).await
+    }
+
+    /// Shares mixed content. Links are normalized like in
+    /// [`share_url`](Self::share_url), so local URLs cannot be shared, and
+    /// HTML is sanitized like in [`share_html`](Self::share_html). With
+    /// `strip_metadata`, files and bytes are shared without privacy metadata.
+    pub async fn share(
+        &self,
+        window: WebviewWindow<R>,
+        mut items: Vec<ShareItem>,
+        mut options: ShareOptions,
+    ) -> crate::Result<()> {
+        if items.is_empty() {
+            return Err(Error::InvalidInput("nothing to share".into()));
+        }
+        options.title = self.config.title(options.title);
+        if self.config.strip_metadata(options.strip_metadata.take()) {
+            items = self.strip_items(items).await?;
+        }
+        for item in &mut items {
+            match item {
+                ShareItem::Url { url } => *url = link::normalize(url)?,
+                ShareItem::Html { html } => *html = html::sanitize(html),
+                ShareItem::File { url, .. } => to_file_path(url),
+                ShareItem::Bytes {
+                    data,
+                    name,
+                    mime_type,
+                } if mime_type.is_none() => {
+                    *mime_type = mime::infer(data, name.as_ref(), &self.config.mime_types);
+                }
+                _ => {}
+            }
+        }
+        self.infer_mime_types(
+            items
+                .iter_mut()
+                .filter_map(|item| match item {
+                    ShareItem::File { url, mime_type } => Some((url.as_str(), mime_type)),
+                    _ => None,
+                })
+                .collect(),
+        )
+        .await?;
+        self.backend.share(window, items, options).await
+    }
+
+    /// Writes `data` to a staged file, or only its encryption with an
+    /// `encryption`, and shares it like [`share_file`](Self::share_file).
+    /// With `image` options, an image is resized or converted first, and
+    /// with `strip_metadata`, the privacy metadata is removed.
+    pub async fn share_data(
+        &self,
+        window: WebviewWindow<R>,
+        data: &[u8],
+        mut options: ShareDataOptions,
+    ) -> crate::Result<()> {
+        let processed = match options.image.take() {
+            Some(image) => {
+                imaging::check(&image)?;
+                let (data, name) = (data.to_vec(), options.name.clone());
+                self.staging_task(move |_| imaging::process(&data, name.as_ref(), &image))
+                    .await?
+            }
+            None => None,
+        };
+        // Re-encoded images carry no metadata.
+        let (data, strip) = match processed {
+            Some(image) => {
+                options.name = image.file_name(&options.name);
+                options.mime_type = Some(imaging::mime_type(image.format).into());
+                (image.data, false)
+            }
+            None => (
+                data.to_vec(),
+                self.config.strip_metadata(options.strip_metadata),
+            ),
+        };
+        let encryption = options.encryption.take();
+        if let Some(encryption) = &encryption {
+            options.mime_type = Some(encryption.mime_type().into());
+        } else if options.mime_type.is_none() {
+            options.mime_type = mime::infer(&data, options.name.as_ref(), &self.config.mime_types);
+        }
+        let name = options.name.clone();
+        let path = self
+            .staging_task(move |staging| {
+                let data = if strip {
+                    metadata::strip(&data)?.unwrap_or(data)
+                } else {
+                    data
+                };
+                encryption.map_or_else(
+                    || staging.stage_bytes(&name, &data),
+                    |encryption| {
+                        let len = Some(data.len() as u64);
+                        staging.stage_encrypted(data.as_slice(), len, &name, &encryption)
+                    },
+                )
+            })
+            .await?;
+        self.share_file(
+            window,
+            path.to_string_lossy().into_owned(),
+            ShareFileOptions {
+                mime_type: options.mime_type,
+                title: options.title,
+                position: options.position,
+                strip_metadata: Some(false),
+                ..ShareFileOptions::default()
+            },
+        )
+        .await
+    }
+
+    /// Encodes `image` as PNG, or the format of the options, into a staged
+    /// file and shares it like [`share_file`](Self::share_file).
+    pub async fn share_image(
+        &self,
+        window: WebviewWindow<R>,
+        image: Image<'_>,
+        options: ShareImageOptions,
+    ) -> crate::Result<()> {
+        imaging::check(&ImageOptions {
+            quality: options.quality,
+            ..ImageOptions::default()
+        })?;
+        let (width, height) = (image.width(), image.height());
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidInput("the image has no pixels".into()));
+        }
+        let len = u64::from(width) * u64::from(height) * 4;
+        if image.rgba().len() as u64 != len {
+            return Err(Error::InvalidInput(format!(
+                "expected {len} bytes of RGBA pixels for a {width}x{height} image, got {}",
+                image.rgba().len()
+            )));
+        }
+
+        let format = options.format.unwrap_or(ImageFormat::Png);
+        let quality = options.quality;
+        let image = image.to_owned();
+        let name = options.name.unwrap_or_else(|| "Image".into());
+        let path = self
+            .staging_task(move |staging| {
+                let encoded = imaging::encode_rgba(image.rgba(), width, height, format, quality)?;
+                staging.stage_bytes(&encoded.file_name(&name), &encoded.data)
+            })
+            .await?;
+        self.share_file(
+            window,
+            path.to_string_lossy().into_owned(),
+            ShareFileOptions {
+                mime_type: Some(imaging::mime_type(format).into()),
+                title: options.title,
+                position: options.position,
+                strip_metadata: Some(false),
+                ..ShareFileOptions::default()
+            },
+        )
+        .await
+    }
+
+    /// Replaces files with staged copies without privacy metadata, and
+    /// removes it from bytes. Content without any is left as it is.
+    async fn strip_items(&self, mut items: Vec<ShareItem>) -> crate::Result<Vec<ShareItem>> {
+        self.staging_task(move |staging| {
+            let paths: Vec<_> = items
+                .iter()
+                .filter_map(|item| match item {
+                    ShareItem::File { url, .. } => Some(scope::file_path(url)),
+                    _ => None,
+                })
+                .collect();
+            let mut stripped = staging
+                .stage_stripped(paths.iter().map(PathBuf::as_path))?
+                .into_iter();
+            for item in &mut items {
+                match item {
+                    ShareItem::File { url, .. } => {
+                        if let Some(path) = stripped.next().flatten() {
+                            *url = path.to_string_lossy().into_owned();
+                        }
+                    }
+                    ShareItem::Bytes { data, .. } => {
+                        if let Some(cleaned) = metadata::strip(data)? {
+                            *data = cleaned;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(items)
+        })
+        .await
+    }
+
+    /// Runs `task` on a blocking thread, for staging work that reads or
+    /// writes whole files. Content the task rejects as
+    /// [`InvalidInput`](std::io::ErrorKind::InvalidInput), such as an image
+    /// too large to render, fails with [`Error::InvalidInput`].
+    pub(crate) async fn staging_task<T: Send + 'static>(
+        &self,
+        task: impl FnOnce(&Staging) -> std::io::Result<T> + Send + 'static,
+    ) -> crate::Result<T> {
+        let staging = self.staging.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || task(&staging))
+            .await
+            .map_err(std::io::Error::other)?;
+        result.map_err(|e| match e.kind() {
+            std::io::ErrorKind::InvalidInput => Error::InvalidInput(e.to_string()),
+            _ => e.into(),
+        })
+    }
+
+    /// Fills in missing MIME types of the files at the URLs from their
+    /// content or extension, so every backend gets one and targets can be
+    /// filtered properly. The files are read on a blocking thread.
+    async fn infer_mime_types(&self, files: Vec<(&str, &mut Option<String>)>) -> crate::Result<()> {
+        let mut missing: Vec<_> = files
+            .into_iter()
+            .filter(|(_, mime_type)| mime_type.is_none())
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let paths: Vec<_> = missing
+            .iter()
+            .map(|(url, _)| scope::file_path(url))
+            .collect();
+        let overrides = self.config.mime_types.clone();
+        let inferred = self
+            .staging_task(move |_| {
+                Ok(paths
+                    .iter()
+                    .map(|path| mime::infer_file(path, &overrides))
+                    .collect::<Vec<_>>())
+            })
+            .await?;
+        for ((_, mime_type), inferred) in missing.iter_mut().zip(inferred) {
+            **mime_type = inferred;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces a `file://` URL with its path: backends are only handed plain
+/// paths, which every platform API takes.
+fn to_file_path(url: &mut String) {
+    *url = scope::file_path(url).to_string_lossy().into_owned();
+}
 
 /// Extensions to [`tauri::App`], [`tauri::AppHandle`], [`tauri::WebviewWindow`], [`tauri::Webview`] and [`tauri::Window`] to access the share APIs.
 pub trait ShareExt<R: Runtime> {
     fn share(&self) -> &ShareKit<R>;
+
+    /// Starts a share presented over this window or webview.
+    fn share_request(&self) -> ShareRequest<'_, R>
+    where
+        Self: ShareParent<R>,
+    {
+        ShareRequest::new(self.share(), self.share_window())
+    }
 }
 
 impl<R: Runtime, T: Manager<R>> crate::ShareExt<R> for T {
@@ -40,25 +593,167 @@ impl<R: Runtime, T: Manager<R>> crate::ShareExt<R> for T {
     }
 }
 
-/// Initializes the plugin.
+type BackendFactory<R> =
+    Box<dyn FnOnce(Box<dyn ShareBackend<R>>) -> Box<dyn ShareBackend<R>> + Send>;
+
+/// Builder for the plugin, for apps that replace or wrap the built-in
+/// share backend.
+///
+/// ```no_run
+/// use tauri::{Runtime, WebviewWindow};
+/// use tauri_plugin_sharekit::{ShareBackend, ShareFuture, ShareItem, ShareOptions};
+///
+/// struct Audited<B>(B);
+///
+/// impl<R: Runtime, B: ShareBackend<R>> ShareBackend<R> for Audited<B> {
+///     fn share(
+///         &self,
+///         window: WebviewWindow<R>,
+///         items: Vec<ShareItem>,
+///         options: ShareOptions,
+///     ) -> ShareFuture<'_> {
+///         println!("sharing {} items", items.len());
+///         self.0.share(window, items, options)
+///     }
+/// }
+///
+/// tauri::Builder::default()
+///     .plugin(tauri_plugin_sharekit::Builder::new().wrap_backend(Audited).build());
+/// ```
+pub struct Builder<R: Runtime> {
+    backend: Option<BackendFactory<R>>,
+    encryptions: BTreeMap<String, Encryption>,
+    web_share_polyfill: bool,
+}
+
+impl<R: Runtime> Default for Builder<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Runtime> Builder<R> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            backend: None,
+            encryptions: BTreeMap::new(),
+            web_share_polyfill: false,
+        }
+    }
+
+    /// Registers `encryption` under `name`, so the webview can have files
+    /// and data shared encrypted by passing `encryption: name`, without
+    /// ever seeing the key.
+    ///
+    /// ```no_run
+    /// use tauri_plugin_sharekit::{Builder, Encryption};
+    ///
+    /// # fn run() -> tauri_plugin_sharekit::Result<()> {
+    /// let clinic = Encryption::age(["age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p"])?;
+    /// tauri::Builder::default().plugin(Builder::new().encryption("clinic", clinic).build());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn encryption(mut self, name: impl Into<String>, encryption: Encryption) -> Self {
+        self.encryptions.insert(name.into(), encryption);
+        self
+    }
+
+    /// Provides `navigator.share` and `navigator.canShare`, backed by this
+    /// plugin, in webviews that lack them, like the Windows and Linux ones.
+    /// Pages written against the Web Share API then share through the
+    /// native share sheet unchanged. Webviews that have the API keep their
+    /// own.
+    #[must_use]
+    pub const fn web_share_polyfill(mut self) -> Self {
+        self.web_share_polyfill = true;
+        self
+    }
+
+    /// Uses `backend` instead of the platform's built-in backend.
+    #[must_use]
+    pub fn backend(mut self, backend: impl ShareBackend<R>) -> Self {
+        self.backend = Some(Box::new(move |_| Box::new(backend)));
+        self
+    }
+
+    /// Wraps the platform's built-in backend, e.g. to inspect or veto shares
+    /// before delegating to it.
+    #[must_use]
+    pub fn wrap_backend<B, F>(mut self, wrap: F) -> Self
+    where
+        B: ShareBackend<R>,
+        F: FnOnce(Box<dyn ShareBackend<R>>) -> B + Send + 'static,
+    {
+        self.backend = Some(Box::new(move |native| Box::new(wrap(native))));
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> TauriPlugin<R, Option<Config>> {
+        let mut builder = plugin::Builder::<R, Option<Config>>::new("sharekit");
+        if self.web_share_polyfill {
+            builder = builder.js_init_script(include_str!("web_share.js"));
+        }
+        builder
+            .invoke_handler(tauri::generate_handler![
+                commands::share_text,
+                commands::share_html,
+                commands::share_url,
+                commands::share_file,
+                commands::share_files,
+                commands::share_email,
+                commands::share,
+                commands::share_data,
+                commands::share_image,
+                commands::share_web,
+                commands::get_capabilities,
+                commands::can_share
+            ])
+            .setup(move |app, api| {
+                let config = api.config().clone().unwrap_or_default().resolve(app)?;
+                let staging = Staging::new(app, &config)?;
+                if let Err(e) = staging.sweep() {
+                    log::warn!("failed to remove expired staged files: {e}");
+                }
+
+                #[cfg(mobile)]
+                let native = mobile::init(app, api, config.clone())?;
+                #[cfg(all(
+                    desktop,
+                    not(target_os = "macos"),
+                    not(target_os = "windows"),
+                    not(target_os = "linux")
+                ))]
+                let native = desktop::init(app, api, config.clone())?;
+                #[cfg(target_os = "linux")]
+                let native = linux::init(app, api, config.clone())?;
+                #[cfg(target_os = "macos")]
+                let native = macos::init(app, api, config.clone())?;
+                #[cfg(target_os = "windows")]
+                let native = windows::init(app, api, config.clone())?;
+
+                let native: Box<dyn ShareBackend<R>> = Box::new(native);
+                let backend = match self.backend {
+                    Some(factory) => factory(native),
+                    None => native,
+                };
+                app.manage(ShareKit {
+                    backend,
+                    config,
+                    staging,
+                    encryptions: self.encryptions,
+                });
+                Ok(())
+            })
+            .build()
+    }
+}
+
+/// Initializes the plugin with the platform's built-in backend.
 #[must_use]
-pub fn init<R: Runtime>() -> TauriPlugin<R> {
-    Builder::new("sharekit")
-        .invoke_handler(tauri::generate_handler![
-            commands::share_text,
-            commands::share_file
-        ])
-        .setup(|app, api| {
-            #[cfg(mobile)]
-            let share = mobile::init(app, api)?;
-            #[cfg(all(desktop, not(target_os = "macos"), not(target_os = "windows")))]
-            let share = desktop::init(app, api)?;
-            #[cfg(target_os = "macos")]
-            let share = macos::init(app, api)?;
-            #[cfg(target_os = "windows")]
-            let share = windows::init(app, api)?;
-            app.manage(share);
-            Ok(())
-        })
-        .build()
+pub fn init<R: Runtime>() -> TauriPlugin<R, Option<Config>> {
+    Builder::new().build()
 }
@@ -0,0 +1,58 @@
+use tauri::Url;
+
+use crate::Error;
+
+/// URL schemes that mean nothing outside the app sharing them.
+const LOCAL_SCHEMES: &[&str] = &["about", "blob", "data", "file", "javascript", "ws", "wss"];
+
+/// Parses `url` into its normal form, e.g. with a lowercase host and without
+/// `..` segments, if it can be shared with other apps.
+pub fn normalize(url: &str) -> crate::Result<String> {
+    let parsed = Url::parse(url.trim())
+        .map_err(|e| Error::InvalidInput(format!("invalid URL `{url}`: {e}")))?;
+    if LOCAL_SCHEMES.contains(&parsed.scheme()) {
+        return Err(Error::InvalidInput(format!(
+            "`{}:` URLs cannot be shared",
+            parsed.scheme()
+        )));
+    }
+    Ok(parsed.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_are_normalized() {
+        assert_eq!(
+            normalize(" HTTPS://Example.COM/a/../b?q=1#top ").expect("valid"),
+            "https://example.com/b?q=1#top"
+        );
+        assert_eq!(
+            normalize("https://bücher.example").expect("valid"),
+            "https://xn--bcher-kva.example/"
+        );
+        assert_eq!(
+            normalize("mailto:someone@example.com").expect("valid"),
+            "mailto:someone@example.com"
+        );
+    }
+
+    #[test]
+    fn local_and_invalid_urls_are_rejected() {
+        for url in [
+            "javascript:alert(1)",
+            "file:///etc/passwd",
+            "data:,hi",
+            "blob:https://example.com/1",
+            "example.com",
+            "",
+        ] {
+            assert!(
+                matches!(normalize(url), Err(Error::InvalidInput(_))),
+                "{url} was accepted"
+            );
+        }
+    }
+}
@@ -0,0 +1,1158 @@
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr, OsString};
+use std::fs::File;
+use std::os::fd::AsFd;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use gtk::glib::{self, translate::ToGlibPtr, Cast};
+use gtk::prelude::{ObjectExt, WidgetExt};
+use serde::de::DeserializeOwned;
+use tauri::{plugin::PluginApi, AppHandle, Runtime, Url, WebviewWindow};
+use tokio::sync::{oneshot, OnceCell};
+use zbus::zvariant::{Fd, ObjectPath, OwnedObjectPath, OwnedValue, Value};
+use zbus::Connection;
+
+use crate::config::{Config, Fallback, LinuxBackend};
+use crate::models::{
+    ShareCapabilities, ShareEmailOptions, ShareFileOptions, ShareFilesOptions, ShareHtmlOptions,
+    ShareItem, ShareOptions, ShareTextOptions, ShareUrlOptions, SharedFile,
+};
+use crate::scope::file_path;
+use crate::{html, link, Error, ShareExt};
+
+impl From<zbus::Error> for Error {
+    fn from(err: zbus::Error) -> Self {
+        Self::Portal(err.to_string())
+    }
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.OpenURI",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait OpenUri {
+    #[zbus(name = "OpenURI")]
+    fn open_uri(
+        &self,
+        parent_window: &str,
+        uri: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    fn open_file(
+        &self,
+        parent_window: &str,
+        fd: Fd<'_>,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.Email",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait Email {
+    fn compose_email(
+        &self,
+        parent_window: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.Request",
+    default_service = "org.freedesktop.portal.Desktop"
+)]
+trait Request {
+    #[zbus(signal)]
+    fn response(&self, response: u32, results: HashMap<String, OwnedValue>) -> zbus::Result<()>;
+}
+
+#[allow(clippy::unnecessary_wraps)] // signature required by `lib.rs` plugin setup contract
+pub fn init<R: Runtime, C: DeserializeOwned>(
+    app: &AppHandle<R>,
+    _api: PluginApi<R, C>,
+    config: Config,
+) -> crate::Result<NativeBackend<R>> {
+    Ok(NativeBackend {
+        app: app.clone(),
+        config,
+        connection: OnceCell::new(),
+    })
+}
+
+/// Built-in backend using xdg-desktop-portal or xdg-utils.
+pub struct NativeBackend<R: Runtime> {
+    app: AppHandle<R>,
+    config: Config,
+    connection: OnceCell<Connection>,
+}
+
+impl<R: Runtime> NativeBackend<R> {
+    /// Only the portal tells whether the user picked an application or
+    /// dismissed the dialog; xdg-utils return once the tool is started.
+    pub const fn capabilities(&self) -> ShareCapabilities {
+        let portal = matches!(self.config.linux.backend, LinuxBackend::Portal);
+        ShareCapabilities {
+            text: true,
+            files: true,
+            multiple_files: true,
+            urls: true,
+            reports_result: portal,
+            anchors_position: false,
+            reports_dismissal: portal,
+        }
+    }
+
+    async fn connection(&self) -> crate::Result<&Connection> {
+        self.connection
+            .get_or_try_init(Connection::session)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub async fn share_text(
+        &self,
+        window: WebviewWindow<R>,
+        text: String,
+        options: ShareTextOptions,
+    ) -> crate::Result<()> {
+        self.send(&window, text_target(text, options)).await
+    }
+
+    /// Becomes a new email with the plain text as its body. Neither the
+    /// Email portal nor xdg-email take HTML bodies, so the HTML is attached
+    /// as a `text/html` file.
+    pub async fn share_html(
+        &self,
+        window: WebviewWindow<R>,
+        html: String,
+        text: String,
+        options: ShareHtmlOptions,
+    ) -> crate::Result<()> {
+        let attachment = self
+            .app
+            .share()
+            .staging_task(move |staging| staging.stage_bytes("message.html", html.as_bytes()))
+            .await?;
+        let target = Target::Email {
+            addresses: Vec::new(),
+            cc: Vec::new(),
+            subject: options.subject.or(options.title),
+            body: Some(text),
+            attachments: vec![attachment],
+        };
+        self.send(&window, target).await
+    }
+
+    /// Links are opened with an application the user picks, unless they
+    /// have a title or description to go with them.
+    pub async fn share_url(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        options: ShareUrlOptions,
+    ) -> crate::Result<()> {
+        self.send(&window, url_target(url, options)).await
+    }
+
+    /// Lets the user pick the application the file is opened with.
+    pub async fn share_file(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        _options: ShareFileOptions,
+    ) -> crate::Result<()> {
+        self.send(&window, Target::File(file_path(&url))).await
+    }
+
+    /// Attaches the files to a new email addressed to the recipients.
+    pub async fn share_email(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareEmailOptions,
+    ) -> crate::Result<()> {
+        let target = Target::Email {
+            addresses: options.to,
+            cc: options.cc,
+            subject: options.subject,
+            body: options.body,
+            attachments: files.iter().map(|file| file_path(&file.url)).collect(),
+        };
+        self.send(&window, target).await
+    }
+
+    /// Shares several files at once by attaching them to a new email, since
+    /// `OpenURI` only accepts a single file.
+    pub async fn share_files(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareFilesOptions,
+    ) -> crate::Result<()> {
+        let mut paths: Vec<PathBuf> = files.iter().map(|file| file_path(&file.url)).collect();
+        let target = if paths.len() == 1 {
+            Target::File(paths.remove(0))
+        } else {
+            Target::Email {
+                addresses: Vec::new(),
+                cc: Vec::new(),
+                subject: options.title,
+                body: None,
+                attachments: paths,
+            }
+        };
+        self.send(&window, target).await
+    }
+
+    /// Shares a mix of items. A lone link or file is opened with an
+    /// application the user picks; anything else is collected into a new
+    /// email, with text and links in the body and files as attachments. HTML
+    /// is attached like in [`share_html`](Self::share_html), and its plain
+    /// text becomes the body when there is no other text.
+    pub async fn share(
+        &self,
+        window: WebviewWindow<R>,
+        items: Vec<ShareItem>,
+        options: ShareOptions,
+    ) -> crate::Result<()> {
+        // Neither the Email portal nor xdg-email take HTML bodies.
+        let (items, html_files) = self
+            .app
+            .share()
+            .staging_task(move |staging| {
+                let items = staging.stage_items(items)?;
+                let html_files = items
+                    .iter()
+                    .filter_map(|item| match item {
+                        ShareItem::Html { html } => Some(html),
+                        _ => None,
+                    })
+                    .map(|html| staging.stage_bytes("message.html", html.as_bytes()))
+                    .collect::<std::io::Result<Vec<_>>>()?;
+                Ok((items, html_files))
+            })
+            .await?;
+        let mut html_files = html_files.into_iter();
+
+        let target = match items.as_slice() {
+            [ShareItem::Url { url }] => Target::Uri(url.clone()),
+            [ShareItem::File { url, .. }] => Target::File(file_path(url)),
+            _ => {
+                let mut body = Vec::new();
+                let mut html_text = Vec::new();
+                let mut attachments = Vec::new();
+                for item in items {
+                    match item {
+                        ShareItem::Text { text } => body.push(text),
+                        ShareItem::Url { url } => body.push(url),
+                        ShareItem::Html { html } => {
+                            html_text.push(html::to_plain_text(&html)?);
+                            attachments.extend(html_files.next());
+                        }
+                        ShareItem::File { url, .. } => attachments.push(file_path(&url)),
+                        ShareItem::Bytes { .. } => {}
+                    }
+                }
+                if body.is_empty() {
+                    body = html_text;
+                }
+                Target::Email {
+                    addresses: Vec::new(),
+                    cc: Vec::new(),
+                    subject: options.title,
+                    body: (!body.is_empty()).then(|| body.join("\n\n")),
+                    attachments,
+                }
+            }
+        };
+        self.send(&window, target).await
+    }
+
+    /// Hands `target` to the configured backend, opening lone links and files
+    /// with the default application when that fails and the fallback allows it.
+    async fn send(&self, window: &WebviewWindow<R>, target: Target) -> crate::Result<()> {
+        let result = match self.config.linux.backend {
+            LinuxBackend::Portal => self.send_to_portal(window, &target).await,
+            LinuxBackend::XdgUtils => xdg_send(&target).await,
+        };
+        match result {
+            Err(err) if self.should_fall_back(&err, &target) => xdg_send(&target).await,
+            result => result,
+        }
+    }
+
+    /// Only portal failures fall back: `xdg-open` is what the xdg-utils
+    /// backend uses for links and files already.
+    fn should_fall_back(&self, err: &Error, target: &Target) -> bool {
+        self.config.fallback == Fallback::OpenDefault
+            && self.config.linux.backend == LinuxBackend::Portal
+            && matches!(err, Error::Portal(_))
+            && matches!(target, Target::Uri(_) | Target::File(_))
+    }
+
+    async fn send_to_portal(
+        &self,
+        window: &WebviewWindow<R>,
+        target: &Target,
+    ) -> crate::Result<()> {
+        let connection = self.connection().await?;
+        match target {
+            Target::Uri(uri) => {
+                let parent = ParentWindow::export(window).await;
+                open_uri(connection, parent.handle(), uri).await
+            }
+            Target::File(path) => {
+                let file = File::open(path)?;
+                let parent = ParentWindow::export(window).await;
+                open_file(connection, parent.handle(), &file).await
+            }
+            Target::Email {
+                addresses,
+                cc,
+                subject,
+                body,
+                attachments,
+            } => {
+                let message = EmailMessage {
+                    addresses: addresses.clone(),
+                    cc: cc.clone(),
+                    subject: subject.clone(),
+                    body: body.clone(),
+                    attachments: attachments
+                        .iter()
+                        .map(File::open)
+                        .collect::<std::io::Result<_>>()?,
+                };
+                let parent = ParentWindow::export(window).await;
+                compose_email(connection, parent.handle(), &message).await
+            }
+        }
+    }
+}
+
+/// What a share request is handed to other applications as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    /// A link, opened with an application the user picks.
+    Uri(String),
+    /// A single file, opened with an application the user picks.
+    File(PathBuf),
+    /// A new email in the user's mail client.
+    Email {
+        addresses: Vec<String>,
+        cc: Vec<String>,
+        subject: Option<String>,
+        body: Option<String>,
+        attachments: Vec<PathBuf>,
+    },
+}
+
+/// Links are opened with an application the user picks, anything else
+/// becomes the body of a new email, titled with the subject or title. Local
+/// URLs, such as `file:` URLs, are sent as text so they cannot open files
+/// outside the share scope.
+fn text_target(text: String, options: ShareTextOptions) -> Target {
+    let uri = is_uri(&text).then(|| link::normalize(&text).ok()).flatten();
+    uri.map_or_else(
+        || Target::Email {
+            addresses: Vec::new(),
+            cc: Vec::new(),
+            subject: options.subject.or(options.title),
+            body: Some(text),
+            attachments: Vec::new(),
+        },
+        Target::Uri,
+    )
+}
+
+/// A link with a title or description becomes a new email with the title
+/// as its subject, since `OpenURI` can only pass the link on.
+fn url_target(url: String, options: ShareUrlOptions) -> Target {
+    if options.title.is_none() && options.description.is_none() {
+        return Target::Uri(url);
+    }
+    let body = match options.description {
+        Some(description) => format!("{description}\n\n{url}"),
+        None => url,
+    };
+    Target::Email {
+        addresses: Vec::new(),
+        cc: Vec::new(),
+        subject: options.title,
+        body: Some(body),
+        attachments: Vec::new(),
+    }
+}
+
+/// Whether `text` is a single absolute URI rather than free-form text.
+fn is_uri(text: &str) -> bool {
+    let text = text.trim();
+    !text.contains(char::is_whitespace)
+        && Url::parse(text).is_ok_and(|url| !url.cannot_be_a_base() || url.scheme() == "mailto")
+}
+
+/// Hands `target` to `xdg-open` or `xdg-email`.
+async fn xdg_send(target: &Target) -> crate::Result<()> {
+    let (program, args) = xdg_command(target)?;
+    let status = tokio::process::Command::new(program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| Error::XdgUtils(format!("failed to run {program}: {e}")))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::XdgUtils(format!("{program} exited with {status}")))
+    }
+}
+
+/// Program and arguments for `target`. Paths are made absolute so they
+/// cannot be mistaken for options.
+fn xdg_command(target: &Target) -> std::io::Result<(&'static str, Vec<OsString>)> {
+    Ok(match target {
+        Target::Uri(uri) => ("xdg-open", vec![uri.into()]),
+        Target::File(path) => ("xdg-open", vec![std::path::absolute(path)?.into()]),
+        Target::Email {
+            addresses,
+            cc,
+            subject,
+            body,
+            attachments,
+        } => {
+            let mut args = vec![OsString::from("--utf8")];
+            for address in cc {
+                args.extend(["--cc".into(), address.into()]);
+            }
+            if let Some(subject) = subject {
+                args.extend(["--subject".into(), subject.into()]);
+            }
+            if let Some(body) = body {
+                args.extend(["--body".into(), body.into()]);
+            }
+            for path in attachments {
+                args.extend(["--attach".into(), std::path::absolute(path)?.into()]);
+            }
+            args.extend(addresses.iter().map(OsString::from));
+            ("xdg-email", args)
+        }
+    })
+}
+
+/// Content of a `ComposeEmail` portal request.
+#[derive(Debug, Default)]
+struct EmailMessage {
+    addresses: Vec<String>,
+    cc: Vec<String>,
+    subject: Option<String>,
+    body: Option<String>,
+    attachments: Vec<File>,
+}
+
+async fn open_uri(connection: &Connection, parent_window: &str, uri: &str) -> crate::Result<()> {
+    let proxy = OpenUriProxy::new(connection).await?;
+    let token = handle_token();
+    let mut options = request_options(&token);
+    options.insert("ask", Value::from(true));
+
+    await_response(
+        connection,
+        &token,
+        proxy.open_uri(parent_window, uri, options),
+    )
+    .await
+}
+
+async fn open_file(connection: &Connection, parent_window: &str, file: &File) -> crate::Result<()> {
+    let proxy = OpenUriProxy::new(connection).await?;
+    let token = handle_token();
+    let mut options = request_options(&token);
+    options.insert("ask", Value::from(true));
+
+    await_response(
+        connection,
+        &token,
+        proxy.open_file(parent_window, Fd::from(file.as_fd()), options),
+    )
+    .await
+}
+
+async fn compose_email(
+    connection: &Connection,
+    parent_window: &str,
+    message: &EmailMessage,
+) -> crate::Result<()> {
+    let proxy = EmailProxy::new(connection).await?;
+    let token = handle_token();
+    let mut options = request_options(&token);
+    if !message.addresses.is_empty() {
+        options.insert("addresses", Value::from(message.addresses.clone()));
+    }
+    if !message.cc.is_empty() {
+        options.insert("cc", Value::from(message.cc.clone()));
+    }
+    if let Some(subject) = &message.subject {
+        options.insert("subject", Value::from(subject.as_str()));
+    }
+    if let Some(body) = &message.body {
+        options.insert("body", Value::from(body.as_str()));
+    }
+    if !message.attachments.is_empty() {
+        let fds: Vec<Fd<'_>> = message
+            .attachments
+            .iter()
+            .map(|file| Fd::from(file.as_fd()))
+            .collect();
+        options.insert("attachment_fds", Value::from(fds));
+    }
+
+    await_response(
+        connection,
+        &token,
+        proxy.compose_email(parent_window, options),
+    )
+    .await
+}
+
+fn request_options(token: &str) -> HashMap<&'static str, Value<'_>> {
+    HashMap::from([("handle_token", Value::from(token))])
+}
+
+/// Unique `handle_token` for a portal request, valid as an object path element.
+fn handle_token() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "sharekit_{}_{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Object path the portal will use for the request identified by `token`.
+fn request_path(connection: &Connection, token: &str) -> crate::Result<OwnedObjectPath> {
+    let sender = connection
+        .unique_name()
+        .ok_or_else(|| Error::Portal("connection has no unique name".into()))?
+        .trim_start_matches(':')
+        .replace('.', "_");
+    let path = format!("/org/freedesktop/portal/desktop/request/{sender}/{token}");
+    ObjectPath::try_from(path)
+        .map(Into::into)
+        .map_err(|e| Error::Portal(e.to_string()))
+}
+
+/// Performs a portal call and waits for the `Response` signal of the
+/// request object it creates.
+///
+/// The signal subscription is set up before the call is made so a fast
+/// portal cannot answer before we are listening.
+async fn await_response(
+    connection: &Connection,
+    token: &str,
+    call: impl std::future::Future<Output = zbus::Result<OwnedObjectPath>>,
+) -> crate::Result<()> {
+    let expected = request_path(connection, token)?;
+    let request = RequestProxy::builder(connection)
+        .path(expected.clone())?
+        .build()
+        .await?;
+    let mut responses = request.receive_response().await?;
+
+    let handle = call.await?;
+    // Portals older than version 0.9 ignore `handle_token` and pick their own path.
+    if handle != expected {
+        let request = RequestProxy::builder(connection)
+            .path(handle)?
+            .build()
+            .await?;
+        responses = request.receive_response().await?;
+    }
+
+    let response = responses
+        .next()
+        .await
+        .ok_or_else(|| Error::Portal("portal closed the request".into()))?;
+    match response.args()?.response {
+        0 => Ok(()),
+        1 => Err(Error::ShareCancelled),
+        code => Err(Error::Portal(format!("request ended with code {code}"))),
+    }
+}
+
+/// How long the compositor gets to export a Wayland window handle before the
+/// portal request is sent without a parent window.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// `parent_window` identifier for portal requests, as described in the
+/// xdg-desktop-portal "Window Identifiers" documentation.
+///
+/// Wayland handles are exported through xdg-foreign and stay valid until this
+/// value is dropped.
+struct ParentWindow<R: Runtime> {
+    handle: String,
+    window: Option<WebviewWindow<R>>,
+}
+
+impl<R: Runtime> ParentWindow<R> {
+    async fn export(window: &WebviewWindow<R>) -> Self {
+        let (tx, rx) = oneshot::channel::<String>();
+        let target = window.clone();
+        let dispatched = window.run_on_main_thread(move || {
+            let Some(gdk_window) = target.gtk_window().ok().and_then(|w| w.window()) else {
+                let _ = tx.send(String::new());
+                return;
+            };
+            if let Some(x11) = gdk_window.downcast_ref::<gdkx11::X11Window>() {
+                let _ = tx.send(format!("x11:{:x}", x11.xid()));
+            } else if is_wayland(&gdk_window) {
+                export_wayland_handle(&gdk_window, tx);
+            } else {
+                let _ = tx.send(String::new());
+            }
+        });
+        if dispatched.is_err() {
+            return Self {
+                handle: String::new(),
+                window: None,
+            };
+        }
+
+        // A compositor that accepts the export but never answers must not
+        // block the share.
+        let handle = tokio::time::timeout(EXPORT_TIMEOUT, rx)
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .unwrap_or_default();
+        let exported = handle.starts_with("wayland:");
+        Self {
+            handle,
+            window: exported.then(|| window.clone()),
+        }
+    }
+
+    fn handle(&self) -> &str {
+        &self.handle
+    }
+}
+
+impl<R: Runtime> Drop for ParentWindow<R> {
+    fn drop(&mut self) {
+        if let Some(window) = self.window.take() {
+            let target = window.clone();
+            let _ = window.run_on_main_thread(move || {
+                if let Some(gdk_window) = target.gtk_window().ok().and_then(|w| w.window()) {
+                    unsafe {
+                        gdk_wayland_sys::gdk_wayland_window_unexport_handle(wayland_window_ptr(
+                            &gdk_window,
+                        ));
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn wayland_window_ptr(window: &gtk::gdk::Window) -> *mut gdk_wayland_sys::GdkWaylandWindow {
+    let ptr: *mut gtk::gdk::ffi::GdkWindow = window.to_glib_none().0;
+    ptr.cast()
+}
+
+fn is_wayland(window: &gtk::gdk::Window) -> bool {
+    let wayland_type: glib::Type =
+        unsafe { glib::translate::from_glib(gdk_wayland_sys::gdk_wayland_window_get_type()) };
+    window.type_().is_a(wayland_type)
+}
+
+/// Exports `window` through xdg-foreign and sends `wayland:<handle>` once the
+/// compositor answers, or an empty identifier if exporting is not possible.
+fn export_wayland_handle(window: &gtk::gdk::Window, tx: oneshot::Sender<String>) {
+    type Slot = Option<oneshot::Sender<String>>;
+
+    unsafe extern "C" fn exported(
+        _window: *mut gdk_wayland_sys::GdkWaylandWindow,
+        handle: *const c_char,
+        user_data: *mut c_void,
+    ) {
+        let slot = unsafe { &mut *user_data.cast::<Slot>() };
+        if let Some(tx) = slot.take() {
+            let handle = unsafe { CStr::from_ptr(handle) }.to_string_lossy();
+            let _ = tx.send(format!("wayland:{handle}"));
+        }
+    }
+
+    unsafe extern "C" fn destroy(user_data: *mut c_void) {
+        drop(unsafe { Box::from_raw(user_data.cast::<Slot>()) });
+    }
+
+    let user_data = Box::into_raw(Box::new(Some(tx))).cast::<c_void>();
+    let ok = unsafe {
+        gdk_wayland_sys::gdk_wayland_window_export_handle(
+            wayland_window_ptr(window),
+            Some(exported),
+            user_data,
+            Some(destroy),
+        )
+    };
+    if ok == glib::ffi::GFALSE {
+        // GDK does not take ownership of `user_data` when exporting fails.
+        let slot = unsafe { Box::from_raw(user_data.cast::<Slot>()) };
+        if let Some(tx) = *slot {
+            let _ = tx.send(String::new());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+
+    use zbus::object_server::SignalEmitter;
+    use zbus::zvariant::OwnedFd;
+
+    use super::*;
+
+    /// `dbus-daemon` running a private session bus for the duration of a test.
+    struct PrivateBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl PrivateBus {
+        /// Returns `None` when `dbus-daemon` is not installed.
+        fn start() -> Option<Self> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--nopidfile", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let stdout = daemon.stdout.take()?;
+            let mut address = String::new();
+            BufReader::new(stdout).read_line(&mut address).ok()?;
+            Some(Self {
+                daemon,
+                address: address.trim().to_owned(),
+            })
+        }
+
+        async fn connect(&self) -> Connection {
+            zbus::connection::Builder::address(self.address.as_str())
+                .expect("valid bus address")
+                .build()
+                .await
+                .expect("connect to private bus")
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Call {
+        OpenUri {
+            parent_window: String,
+            uri: String,
+            ask: bool,
+        },
+        OpenFile {
+            parent_window: String,
+            contents: String,
+        },
+        ComposeEmail {
+            parent_window: String,
+            addresses: Vec<String>,
+            body: Option<String>,
+            attachments: usize,
+        },
+    }
+
+    /// Fake `org.freedesktop.portal.Desktop` that records calls and answers
+    /// every request with a fixed response code.
+    #[derive(Clone)]
+    struct FakePortal {
+        calls: Arc<Mutex<Vec<Call>>>,
+        response: u32,
+    }
+
+    impl FakePortal {
+        async fn respond(
+            &self,
+            emitter: &SignalEmitter<'_>,
+            sender: Option<zbus::names::UniqueName<'_>>,
+            options: &HashMap<String, OwnedValue>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            let token: String = options
+                .get("handle_token")
+                .and_then(|v| v.try_clone().ok())
+                .and_then(|v| v.try_into().ok())
+                .unwrap_or_default();
+            let sender = sender
+                .map(|s| s.trim_start_matches(':').replace('.', "_"))
+                .unwrap_or_default();
+            let path = OwnedObjectPath::try_from(format!(
+                "/org/freedesktop/portal/desktop/request/{sender}/{token}"
+            ))
+            .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
+
+            let results: HashMap<String, OwnedValue> = HashMap::new();
+            emitter
+                .connection()
+                .emit_signal(
+                    None::<()>,
+                    &path,
+                    "org.freedesktop.portal.Request",
+                    "Response",
+                    &(self.response, results),
+                )
+                .await?;
+            Ok(path)
+        }
+    }
+
+    struct FakeOpenUri(FakePortal);
+
+    #[zbus::interface(name = "org.freedesktop.portal.OpenURI")]
+    impl FakeOpenUri {
+        #[zbus(name = "OpenURI")]
+        async fn open_uri(
+            &self,
+            #[zbus(header)] header: zbus::message::Header<'_>,
+            #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+            parent_window: String,
+            uri: String,
+            options: HashMap<String, OwnedValue>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            let ask = options
+                .get("ask")
+                .and_then(|v| bool::try_from(v).ok())
+                .unwrap_or_default();
+            self.0.calls.lock().expect("lock").push(Call::OpenUri {
+                parent_window,
+                uri,
+                ask,
+            });
+            self.0
+                .respond(&emitter, header.sender().cloned(), &options)
+                .await
+        }
+
+        async fn open_file(
+            &self,
+            #[zbus(header)] header: zbus::message::Header<'_>,
+            #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+            parent_window: String,
+            fd: OwnedFd,
+            options: HashMap<String, OwnedValue>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            let mut contents = String::new();
+            let mut file = File::from(std::os::fd::OwnedFd::from(fd));
+            std::io::Read::read_to_string(&mut file, &mut contents)
+                .map_err(|e| zbus::fdo::Error::IOError(e.to_string()))?;
+            self.0.calls.lock().expect("lock").push(Call::OpenFile {
+                parent_window,
+                contents,
+            });
+            self.0
+                .respond(&emitter, header.sender().cloned(), &options)
+                .await
+        }
+    }
+
+    struct FakeEmail(FakePortal);
+
+    #[zbus::interface(name = "org.freedesktop.portal.Email")]
+    impl FakeEmail {
+        async fn compose_email(
+            &self,
+            #[zbus(header)] header: zbus::message::Header<'_>,
+            #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+            parent_window: String,
+            options: HashMap<String, OwnedValue>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            let addresses = options
+                .get("addresses")
+                .and_then(|v| v.try_clone().ok())
+                .and_then(|v| Vec::<String>::try_from(v).ok())
+                .unwrap_or_default();
+            let body = options
+                .get("body")
+                .and_then(|v| v.try_clone().ok())
+                .and_then(|v| String::try_from(v).ok());
+            let attachments = options.get("attachment_fds").map_or(0, |v| match &**v {
+                Value::Array(fds) => fds.len(),
+                _ => 0,
+            });
+            self.0.calls.lock().expect("lock").push(Call::ComposeEmail {
+                parent_window,
+                addresses,
+                body,
+                attachments,
+            });
+            self.0
+                .respond(&emitter, header.sender().cloned(), &options)
+                .await
+        }
+    }
+
+    async fn serve_portal(bus: &PrivateBus, response: u32) -> (Connection, FakePortal) {
+        let portal = FakePortal {
+            calls: Arc::default(),
+            response,
+        };
+        let server = zbus::connection::Builder::address(bus.address.as_str())
+            .expect("valid bus address")
+            .name("org.freedesktop.portal.Desktop")
+            .expect("valid name")
+            .serve_at(
+                "/org/freedesktop/portal/desktop",
+                FakeOpenUri(portal.clone()),
+            )
+            .expect("serve OpenURI")
+            .serve_at("/org/freedesktop/portal/desktop", FakeEmail(portal.clone()))
+            .expect("serve Email")
+            .build()
+            .await
+            .expect("start fake portal");
+        (server, portal)
+    }
+
+    fn text_message(body: &str) -> EmailMessage {
+        EmailMessage {
+            body: Some(body.into()),
+            ..EmailMessage::default()
+        }
+    }
+
+    macro_rules! private_bus {
+        () => {
+            match PrivateBus::start() {
+                Some(bus) => bus,
+                None => {
+                    eprintln!("dbus-daemon not available, skipping");
+                    return;
+                }
+            }
+        };
+    }
+
+    #[tokio::test]
+    async fn link_is_opened_through_open_uri() {
+        let bus = private_bus!();
+        let (_server, portal) = serve_portal(&bus, 0).await;
+        let client = bus.connect().await;
+
+        open_uri(&client, "x11:2a", "https://tauri.app")
+            .await
+            .expect("share succeeds");
+
+        assert_eq!(
+            *portal.calls.lock().expect("lock"),
+            vec![Call::OpenUri {
+                parent_window: "x11:2a".into(),
+                uri: "https://tauri.app".into(),
+                ask: true,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn file_is_passed_as_descriptor() {
+        let bus = private_bus!();
+        let (_server, portal) = serve_portal(&bus, 0).await;
+        let client = bus.connect().await;
+
+        let path = std::env::temp_dir().join(format!("sharekit-{}.txt", handle_token()));
+        File::create(&path)
+            .and_then(|mut f| f.write_all(b"shared contents"))
+            .expect("write fixture");
+        let file = File::open(&path).expect("open fixture");
+        let result = open_file(&client, "", &file).await;
+        let _ = std::fs::remove_file(&path);
+
+        result.expect("share succeeds");
+        assert_eq!(
+            *portal.calls.lock().expect("lock"),
+            vec![Call::OpenFile {
+                parent_window: String::new(),
+                contents: "shared contents".into(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn text_becomes_email_body() {
+        let bus = private_bus!();
+        let (_server, portal) = serve_portal(&bus, 0).await;
+        let client = bus.connect().await;
+
+        compose_email(&client, "wayland:abc", &text_message("Hello"))
+            .await
+            .expect("share succeeds");
+
+        assert_eq!(
+            *portal.calls.lock().expect("lock"),
+            vec![Call::ComposeEmail {
+                parent_window: "wayland:abc".into(),
+                addresses: Vec::new(),
+                body: Some("Hello".into()),
+                attachments: 0,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn files_become_email_attachments() {
+        let bus = private_bus!();
+        let (_server, portal) = serve_portal(&bus, 0).await;
+        let client = bus.connect().await;
+
+        let message = EmailMessage {
+            addresses: vec!["ana@example.com".into()],
+            attachments: vec![
+                File::open("Cargo.toml").expect("open fixture"),
+                File::open("build.rs").expect("open fixture"),
+            ],
+            ..EmailMessage::default()
+        };
+        compose_email(&client, "", &message)
+            .await
+            .expect("share succeeds");
+
+        assert_eq!(
+            *portal.calls.lock().expect("lock"),
+            vec![Call::ComposeEmail {
+                parent_window: String::new(),
+                addresses: vec!["ana@example.com".into()],
+                body: None,
+                attachments: 2,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn dismissed_dialog_is_cancellation() {
+        let bus = private_bus!();
+        let (_server, _portal) = serve_portal(&bus, 1).await;
+        let client = bus.connect().await;
+
+        let result = compose_email(&client, "", &text_message("Hello")).await;
+        assert!(matches!(result, Err(Error::ShareCancelled)));
+    }
+
+    #[tokio::test]
+    async fn other_response_is_portal_error() {
+        let bus = private_bus!();
+        let (_server, _portal) = serve_portal(&bus, 2).await;
+        let client = bus.connect().await;
+
+        let result = open_uri(&client, "", "https://tauri.app").await;
+        assert!(matches!(result, Err(Error::Portal(_))));
+    }
+
+    #[test]
+    fn links_with_a_description_become_emails() {
+        assert_eq!(
+            url_target("https://tauri.app/".into(), ShareUrlOptions::default()),
+            Target::Uri("https://tauri.app/".into())
+        );
+        assert_eq!(
+            url_target(
+                "https://tauri.app/".into(),
+                ShareUrlOptions {
+                    title: Some("Tauri".into()),
+                    description: Some("Build smaller apps".into()),
+                    ..ShareUrlOptions::default()
+                }
+            ),
+            Target::Email {
+                addresses: Vec::new(),
+                cc: Vec::new(),
+                subject: Some("Tauri".into()),
+                body: Some("Build smaller apps\n\nhttps://tauri.app/".into()),
+                attachments: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn local_urls_in_text_become_emails() {
+        assert_eq!(
+            text_target(" https://tauri.app ".into(), ShareTextOptions::default()),
+            Target::Uri("https://tauri.app/".into())
+        );
+        assert_eq!(
+            text_target(
+                "file:///home/user/.ssh/id_rsa".into(),
+                ShareTextOptions::default()
+            ),
+            Target::Email {
+                addresses: Vec::new(),
+                cc: Vec::new(),
+                subject: None,
+                body: Some("file:///home/user/.ssh/id_rsa".into()),
+                attachments: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn detects_uris() {
+        assert!(is_uri("https://tauri.app/"));
+        assert!(is_uri("  mailto:someone@example.com "));
+        assert!(!is_uri("Check out https://tauri.app"));
+        assert!(!is_uri("just text"));
+    }
+
+    #[test]
+    fn xdg_utils_open_links_and_files() {
+        assert_eq!(
+            xdg_command(&Target::Uri("https://tauri.app".into())).expect("command"),
+            ("xdg-open", vec![OsString::from("https://tauri.app")])
+        );
+
+        let (program, args) = xdg_command(&Target::File("-report.pdf".into())).expect("command");
+        assert_eq!(program, "xdg-open");
+        let cwd = std::env::current_dir().expect("current dir");
+        assert_eq!(args, vec![OsString::from(cwd.join("-report.pdf"))]);
+    }
+
+    #[test]
+    fn xdg_utils_compose_emails() {
+        let target = Target::Email {
+            addresses: vec!["ana@example.com".into(), "bo@example.com".into()],
+            cc: vec!["cy@example.com".into()],
+            subject: Some("Trip".into()),
+            body: Some("Photos attached".into()),
+            attachments: vec!["/tmp/a.jpg".into(), "/tmp/b.jpg".into()],
+        };
+        assert_eq!(
+            xdg_command(&target).expect("command"),
+            (
+                "xdg-email",
+                ["--utf8", "--cc", "cy@example.com", "--subject", "Trip"]
+                    .into_iter()
+                    .chain(["--body", "Photos attached"])
+                    .chain(["--attach", "/tmp/a.jpg", "--attach", "/tmp/b.jpg"])
+                    .chain(["ana@example.com", "bo@example.com"])
+                    .map(OsString::from)
+                    .collect()
+            )
+        );
+    }
+}
@@ -5,19 +5,30 @@ use tauri::WebviewWindow;
 use tauri::{plugin::PluginApi, AppHandle, Runtime};
 use tokio::sync::oneshot;
 
-use crate::models::{RectEdge, ShareFileOptions, SharePosition, ShareTextOptions};
+use crate::config::Config;
+use crate::eml::{self, Attachment};
+use crate::models::{
+    RectEdge, ShareCapabilities, ShareEmailOptions, ShareFileOptions, ShareFilesOptions,
+    ShareHtmlOptions, ShareItem, ShareOptions, SharePosition, ShareTextOptions, ShareUrlOptions,
+    SharedFile,
+};
+use crate::scope::file_path;
+use crate::ShareExt;
 
 use objc2::{
     define_class, msg_send,
     rc::Retained,
-    runtime::{AnyObject, NSObject, ProtocolObject},
+    runtime::{AnyClass, AnyObject, NSObject, ProtocolObject},
     AnyThread, DefinedClass,
 };
 use objc2_app_kit::{
-    NSSharingService, NSSharingServicePicker, NSSharingServicePickerDelegate, NSView,
+    NSAttributedStringAppKitDocumentFormats, NSImage, NSPreviewRepresentingActivityItem,
+    NSSharingService, NSSharingServicePicker, NSSharingServicePickerDelegate, NSView, NSWorkspace,
 };
 use objc2_core_foundation::{CGPoint, CGSize};
-use objc2_foundation::{NSArray, NSObjectProtocol, NSRect, NSRectEdge, NSString, NSURL};
+use objc2_foundation::{
+    NSArray, NSAttributedString, NSData, NSObjectProtocol, NSRect, NSRectEdge, NSString, NSURL,
+};
 
 impl From<RectEdge> for NSRectEdge {
     fn from(edge: RectEdge) -> Self {
@@ -39,8 +50,82 @@ fn position_to_rect(position: Option<&SharePosition>) -> (f64, f64, NSRectEdge)
     })
 }
 
+/// Builds the `NSSharingServicePicker` item for a single share item.
+/// `Bytes` items must have been staged to files beforehand.
+fn share_item_object(item: &ShareItem) -> Option<Retained<AnyObject>> {
+    let object: Retained<AnyObject> = match item {
+        ShareItem::Text { text } => unsafe { Retained::cast_unchecked(NSString::from_str(text)) },
+        ShareItem::Url { url } => {
+            let ns_string = NSString::from_str(url);
+            NSURL::URLWithString(&ns_string).map_or_else(
+                || unsafe { Retained::cast_unchecked(ns_string) },
+                |ns_url| unsafe { Retained::cast_unchecked(ns_url) },
+            )
+        }
+        ShareItem::File { url, .. } => {
+            let ns_url = NSURL::fileURLWithPath(&NSString::from_str(url));
+            unsafe { Retained::cast_unchecked(ns_url) }
+        }
+        ShareItem::Html { html } => {
+            let data = NSData::with_bytes(html.as_bytes());
+            let attributed = unsafe {
+                NSAttributedString::initWithHTML_documentAttributes(
+                    NSAttributedString::alloc(),
+                    &data,
+                    None,
+                )
+            }?;
+            unsafe { Retained::cast_unchecked(attributed) }
+        }
+        ShareItem::Bytes { .. } => return None,
+    };
+    Some(object)
+}
+
+/// `NSURL` for `url`, shown with its title and thumbnail in the picker's
+/// preview.
+fn url_object(url: &str, options: &ShareUrlOptions) -> Retained<AnyObject> {
+    let ns_string = NSString::from_str(url);
+    let item: Retained<AnyObject> = NSURL::URLWithString(&ns_string).map_or_else(
+        || unsafe { Retained::cast_unchecked(ns_string) },
+        |ns_url| unsafe { Retained::cast_unchecked(ns_url) },
+    );
+    with_preview(item, options.title.as_deref(), options.thumbnail.as_deref())
+}
+
+/// `item` with a title and thumbnail for the picker's preview, where macOS
+/// 13 and later can show one.
+fn with_preview(
+    item: Retained<AnyObject>,
+    title: Option<&str>,
+    thumbnail: Option<&str>,
+) -> Retained<AnyObject> {
+    let has_preview = title.is_some() || thumbnail.is_some();
+    if !has_preview || AnyClass::get(c"NSPreviewRepresentingActivityItem").is_none() {
+        return item;
+    }
+
+    let title = title.map(NSString::from_str);
+    let image = thumbnail.and_then(|thumbnail| {
+        let path = NSString::from_str(&file_path(thumbnail).to_string_lossy());
+        NSImage::initWithContentsOfFile(NSImage::alloc(), &path)
+    });
+    let preview = unsafe {
+        NSPreviewRepresentingActivityItem::initWithItem_title_image_icon(
+            NSPreviewRepresentingActivityItem::alloc(),
+            &item,
+            title.as_deref(),
+            image.as_deref(),
+            None,
+        )
+    };
+    unsafe { Retained::cast_unchecked(preview) }
+}
+
 struct PickerDelegateIvars {
     sender: Cell<Option<oneshot::Sender<crate::Result<()>>>>,
+    /// Subject of emails and other services that take one.
+    subject: Option<String>,
     picker: RefCell<Option<Retained<NSSharingServicePicker>>>,
     retainer: RefCell<Option<Retained<PickerDelegate>>>,
 }
@@ -60,6 +145,9 @@ define_class!(
             _picker: &NSSharingServicePicker,
             service: Option<&NSSharingService>,
         ) {
+            if let (Some(service), Some(subject)) = (service, &self.ivars().subject) {
+                service.setSubject(Some(&NSString::from_str(subject)));
+            }
             if let Some(sender) = self.ivars().sender.take() {
                 let result = if service.is_some() {
                     Ok(())
@@ -77,9 +165,10 @@ define_class!(
 );
 
 impl PickerDelegate {
-    fn new(sender: oneshot::Sender<crate::Result<()>>) -> Retained<Self> {
+    fn new(sender: oneshot::Sender<crate::Result<()>>, subject: Option<String>) -> Retained<Self> {
         let this = Self::alloc().set_ivars(PickerDelegateIvars {
             sender: Cell::new(Some(sender)),
+            subject,
             picker: RefCell::new(None),
             retainer: RefCell::new(None),
         });
@@ -91,18 +180,22 @@ impl PickerDelegate {
 pub fn init<R: Runtime, C: DeserializeOwned>(
     app: &AppHandle<R>,
     _api: PluginApi<R, C>,
-) -> crate::Result<ShareKit<R>> {
-    Ok(ShareKit(app.clone()))
+    _config: Config,
+) -> crate::Result<NativeBackend<R>> {
+    Ok(NativeBackend { app: app.clone() })
 }
 
-/// Access to the share APIs.
-pub struct ShareKit<R: Runtime>(AppHandle<R>);
+/// Built-in backend using `NSSharingServicePicker`.
+pub struct NativeBackend<R: Runtime> {
+    app: AppHandle<R>,
+}
 
 #[allow(clippy::needless_pass_by_value)] // matches public API signatures of `share_text` / `share_file`
 fn present_picker<R: Runtime>(
     window: WebviewWindow<R>,
     items_builder: impl FnOnce() -> Vec<Retained<AnyObject>> + Send + 'static,
     position: Option<SharePosition>,
+    subject: Option<String>,
 ) -> crate::Result<oneshot::Receiver<crate::Result<()>>> {
     let (tx, rx) = oneshot::channel();
     let (x, y, edge) = position_to_rect(position.as_ref());
@@ -119,7 +212,7 @@ fn present_picker<R: Runtime>(
                 NSSharingServicePicker::initWithItems(NSSharingServicePicker::alloc(), &items_array)
             };
 
-            let delegate = PickerDelegate::new(tx);
+            let delegate = PickerDelegate::new(tx, subject);
             picker.setDelegate(Some(ProtocolObject::from_ref(&*delegate)));
 
             // Self-retain delegate + retain picker so they outlive this closure
@@ -138,20 +231,82 @@ fn present_picker<R: Runtime>(
     Ok(rx)
 }
 
-impl<R: Runtime> ShareKit<R> {
+impl<R: Runtime> NativeBackend<R> {
+    /// The sharing service picker takes any content, is shown at the
+    /// share's position and tells whether a service was chosen.
+    #[allow(clippy::unused_self)] // same signature as the backends that depend on their config
+    pub const fn capabilities(&self) -> ShareCapabilities {
+        ShareCapabilities {
+            text: true,
+            files: true,
+            multiple_files: true,
+            urls: true,
+            reports_result: true,
+            anchors_position: true,
+            reports_dismissal: true,
+        }
+    }
+
     pub async fn share_text(
         &self,
         window: WebviewWindow<R>,
         text: String,
         options: ShareTextOptions,
     ) -> crate::Result<()> {
+        let title = options.title;
         let rx = present_picker(
             window,
             move || {
                 let ns_string = NSString::from_str(&text);
-                vec![unsafe { Retained::cast_unchecked(ns_string) }]
+                let item = unsafe { Retained::cast_unchecked(ns_string) };
+                vec![with_preview(item, title.as_deref(), None)]
             },
             options.position,
+            options.subject,
+        )?;
+
+        rx.await.unwrap_or(Err(crate::Error::ShareCancelled))
+    }
+
+    /// Shares the HTML as an attributed string, which services that only
+    /// take plain text read the text of. `text` is shared instead if the
+    /// HTML cannot be read.
+    pub async fn share_html(
+        &self,
+        window: WebviewWindow<R>,
+        html: String,
+        text: String,
+        options: ShareHtmlOptions,
+    ) -> crate::Result<()> {
+        let title = options.title;
+        let rx = present_picker(
+            window,
+            move || {
+                let item = share_item_object(&ShareItem::Html { html }).unwrap_or_else(|| {
+                    let ns_string = NSString::from_str(&text);
+                    unsafe { Retained::cast_unchecked(ns_string) }
+                });
+                vec![with_preview(item, title.as_deref(), None)]
+            },
+            options.position,
+            options.subject,
+        )?;
+
+        rx.await.unwrap_or(Err(crate::Error::ShareCancelled))
+    }
+
+    pub async fn share_url(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        mut options: ShareUrlOptions,
+    ) -> crate::Result<()> {
+        let position = options.position.take();
+        let rx = present_picker(
+            window,
+            move || vec![url_object(&url, &options)],
+            position,
+            None,
         )?;
 
         rx.await.unwrap_or(Err(crate::Error::ShareCancelled))
@@ -170,6 +325,82 @@ impl<R: Runtime> ShareKit<R> {
                 vec![unsafe { Retained::cast_unchecked(ns_url) }]
             },
             options.position,
+            None,
+        )?;
+
+        rx.await.unwrap_or(Err(crate::Error::ShareCancelled))
+    }
+
+    pub async fn share_files(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareFilesOptions,
+    ) -> crate::Result<()> {
+        let rx = present_picker(
+            window,
+            move || {
+                files
+                    .iter()
+                    .map(|file| {
+                        let ns_url = NSURL::fileURLWithPath(&NSString::from_str(&file.url));
+                        unsafe { Retained::cast_unchecked(ns_url) }
+                    })
+                    .collect()
+            },
+            options.position,
+            None,
+        )?;
+
+        rx.await.unwrap_or(Err(crate::Error::ShareCancelled))
+    }
+
+    /// Opens a new email in the default mail client, written as a draft
+    /// `.eml` message with the files attached, since the sharing services
+    /// cannot address one.
+    pub async fn share_email(
+        &self,
+        _window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareEmailOptions,
+    ) -> crate::Result<()> {
+        let staging = self.app.share().staging().clone();
+        let path = tokio::task::spawn_blocking(move || -> crate::Result<_> {
+            let attachments = files
+                .iter()
+                .map(Attachment::read)
+                .collect::<crate::Result<Vec<_>>>()?;
+            let message = eml::build(&options, &attachments);
+            Ok(staging.stage_bytes(eml::FILE_NAME, &message)?)
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+        if NSWorkspace::sharedWorkspace().openURL(&url) {
+            Ok(())
+        } else {
+            // No application opens `.eml` files.
+            Err(crate::Error::UnsupportedPlatform)
+        }
+    }
+
+    pub async fn share(
+        &self,
+        window: WebviewWindow<R>,
+        items: Vec<ShareItem>,
+        options: ShareOptions,
+    ) -> crate::Result<()> {
+        let items = self
+            .app
+            .share()
+            .staging_task(move |staging| staging.stage_items(items))
+            .await?;
+        let rx = present_picker(
+            window,
+            move || items.iter().filter_map(share_item_object).collect(),
+            options.position,
+            None,
         )?;
 
         rx.await.unwrap_or(Err(crate::Error::ShareCancelled))
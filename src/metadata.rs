@@ -0,0 +1,562 @@
+use std::fs::File;
+use std::io::{self, Cursor, Read, Write};
+use std::path::Path;
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Formats privacy metadata can be removed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Jpeg,
+    Png,
+    Webp,
+    Pdf,
+    /// A zip archive, cleaned if it is an Office Open XML document.
+    Zip,
+}
+
+fn format(data: &[u8]) -> Option<Format> {
+    if data.starts_with(b"\xff\xd8\xff") {
+        Some(Format::Jpeg)
+    } else if data.starts_with(PNG_SIGNATURE) {
+        Some(Format::Png)
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        Some(Format::Webp)
+    } else if data.starts_with(b"%PDF-") {
+        Some(Format::Pdf)
+    } else if data.starts_with(b"PK\x03\x04") {
+        Some(Format::Zip)
+    } else {
+        None
+    }
+}
+
+/// Content of the file at `path` without privacy metadata, or `None` if it
+/// is not in a format metadata can be removed from. Only the first bytes of
+/// such files are read.
+pub fn strip_file(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    let mut file = File::open(path)?;
+    let mut header = Vec::with_capacity(12);
+    (&mut file).take(12).read_to_end(&mut header)?;
+    if format(&header).is_none() {
+        return Ok(None);
+    }
+    let mut data = header;
+    file.read_to_end(&mut data)?;
+    strip(&data)
+}
+
+/// `data` without privacy metadata, or `None` if it is not in a format
+/// metadata can be removed from:
+///
+/// - JPEG: Exif, XMP, IPTC and comments. The Exif orientation is kept, so
+///   photos are not shown rotated.
+/// - PNG: text chunks, Exif and the modification time.
+/// - WebP: Exif and XMP.
+/// - PDF: the document information dictionary and every XMP stream.
+/// - Office Open XML (`.docx`, `.xlsx`, `.pptx`, ...): the core properties,
+///   such as author, last editor and dates.
+pub fn strip(data: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    match format(data) {
+        Some(Format::Jpeg) => strip_jpeg(data).map(Some),
+        Some(Format::Png) => strip_png(data).map(Some),
+        Some(Format::Webp) => strip_webp(data).map(Some),
+        Some(Format::Pdf) => strip_pdf(data).map(Some),
+        Some(Format::Zip) => strip_ooxml(data),
+        None => Ok(None),
+    }
+}
+
+fn malformed(format: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("malformed {format} file"),
+    )
+}
+
+fn strip_jpeg(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..2]);
+    let mut pos = 2;
+    loop {
+        // Markers may be preceded by any number of 0xff fill bytes.
+        while data.get(pos) == Some(&0xff) && data.get(pos + 1) == Some(&0xff) {
+            pos += 1;
+        }
+        let (Some(0xff), Some(&marker)) = (data.get(pos).copied(), data.get(pos + 1)) else {
+            return Err(malformed("JPEG"));
+        };
+        pos += 2;
+        match marker {
+            0xd9 => {
+                out.extend_from_slice(&[0xff, 0xd9]);
+                return Ok(out);
+            }
+            0x01 | 0xd0..=0xd7 => {
+                out.extend_from_slice(&[0xff, marker]);
+                continue;
+            }
+            // Start of scan: the compressed image data follows, and no
+            // metadata comes after it in practice.
+            0xda => {
+                out.extend_from_slice(&data[pos - 2..]);
+                return Ok(out);
+            }
+            _ => {}
+        }
+
+        let len = data
+            .get(pos..pos + 2)
+            .map(|len| usize::from(u16::from_be_bytes([len[0], len[1]])))
+            .filter(|&len| len >= 2)
+            .ok_or_else(|| malformed("JPEG"))?;
+        let segment = data
+            .get(pos - 2..pos + len)
+            .ok_or_else(|| malformed("JPEG"))?;
+        let payload = &segment[4..];
+        pos += len;
+
+        match marker {
+            // JFIF, ICC profiles and Adobe color transforms are needed to
+            // display the image as intended.
+            0xe0 | 0xee => out.extend_from_slice(segment),
+            0xe2 if payload.starts_with(b"ICC_PROFILE\0") => out.extend_from_slice(segment),
+            0xe1 => {
+                if let Some(orientation) = payload
+                    .strip_prefix(b"Exif\0\0")
+                    .and_then(exif_orientation)
+                    .filter(|&orientation| orientation != 1)
+                {
+                    out.extend_from_slice(&orientation_exif(orientation));
+                }
+            }
+            0xe1..=0xef | 0xfe => {}
+            _ => out.extend_from_slice(segment),
+        }
+    }
+}
+
+/// Orientation tag of the first IFD of the TIFF structure in `tiff`.
+fn exif_orientation(tiff: &[u8]) -> Option<u16> {
+    let big_endian = match tiff.get(..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+    let u16_at = |pos: usize| {
+        let bytes = [*tiff.get(pos)?, *tiff.get(pos + 1)?];
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let u32_at = |pos: usize| {
+        let bytes: [u8; 4] = tiff.get(pos..pos + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+
+    let ifd = usize::try_from(u32_at(4)?).ok()?;
+    (0..usize::from(u16_at(ifd)?))
+        .map(|i| ifd + 2 + i * 12)
+        .find(|&entry| u16_at(entry) == Some(0x0112))
+        .and_then(|entry| u16_at(entry + 8))
+}
+
+/// APP1 segment whose Exif data only holds `orientation`.
+fn orientation_exif(orientation: u16) -> Vec<u8> {
+    let mut segment = vec![0xff, 0xe1, 0x00, 0x22];
+    segment.extend_from_slice(b"Exif\0\0MM\0\x2a\0\0\0\x08");
+    // One IFD entry: tag 0x0112, type SHORT, count 1, value.
+    segment.extend_from_slice(&[0x00, 0x01, 0x01, 0x12, 0x00, 0x03, 0, 0, 0, 1]);
+    segment.extend_from_slice(&orientation.to_be_bytes());
+    // Value padding and the offset of the next IFD, of which there is none.
+    segment.extend_from_slice(&[0; 6]);
+    segment
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn strip_png(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(PNG_SIGNATURE);
+    let mut pos = PNG_SIGNATURE.len();
+    while pos < data.len() {
+        let header = data.get(pos..pos + 8).ok_or_else(|| malformed("PNG"))?;
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| (pos + 12).checked_add(len))
+            .filter(|&end| end <= data.len())
+            .ok_or_else(|| malformed("PNG"))?;
+        let kind = &header[4..8];
+        if !matches!(kind, b"tEXt" | b"zTXt" | b"iTXt" | b"eXIf" | b"tIME") {
+            out.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+        if kind == b"IEND" {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+fn strip_webp(data: &[u8]) -> io::Result<Vec<u8>> {
+    /// VP8X flags announcing Exif and XMP chunks.
+    const EXIF_FLAG: u8 = 0x08;
+    const XMP_FLAG: u8 = 0x04;
+
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(b"RIFF\0\0\0\0WEBP");
+    let mut pos = 12;
+    while pos < data.len() {
+        let header = data.get(pos..pos + 8).ok_or_else(|| malformed("WebP"))?;
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        // Chunks are padded to an even size.
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| (pos + 8).checked_add(len + len % 2))
+            .ok_or_else(|| malformed("WebP"))?
+            .min(data.len());
+        let chunk = &data[pos..end];
+        match &header[..4] {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" if chunk.len() > 8 => {
+                out.extend_from_slice(chunk);
+                let flags = out.len() - chunk.len() + 8;
+                out[flags] &= !(EXIF_FLAG | XMP_FLAG);
+            }
+            _ => out.extend_from_slice(chunk),
+        }
+        pos = end;
+    }
+    let riff_len = u32::try_from(out.len() - 8).map_err(|_| malformed("WebP"))?;
+    out[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    Ok(out)
+}
+
+fn strip_pdf(data: &[u8]) -> io::Result<Vec<u8>> {
+    let invalid = |e: lopdf::Error| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+    let mut document = lopdf::Document::load_mem(data).map_err(invalid)?;
+    if document.is_encrypted() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "metadata cannot be removed from encrypted PDF files",
+        ));
+    }
+
+    document.trailer.remove(b"Info");
+    for object in document.objects.values_mut() {
+        let dictionary = match object {
+            lopdf::Object::Dictionary(dictionary) => dictionary,
+            lopdf::Object::Stream(stream) => &mut stream.dict,
+            _ => continue,
+        };
+        // XMP packets, and private data of the application that made the
+        // file, which may name its user.
+        dictionary.remove(b"Metadata");
+        dictionary.remove(b"PieceInfo");
+    }
+    document.prune_objects();
+
+    let mut out = Vec::with_capacity(data.len());
+    document.save_to(&mut out)?;
+    Ok(out)
+}
+
+/// Part of an Office Open XML package holding its core properties.
+const CORE_PROPERTIES: &str = "docProps/core.xml";
+
+/// Core properties part without any properties.
+const EMPTY_CORE_PROPERTIES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    "\r\n",
+    r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" "#,
+    r#"xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" "#,
+    r#"xmlns:dcmitype="http://purl.org/dc/dcmitype/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"/>"#,
+);
+
+/// Replaces the core properties of an Office Open XML document; other zip
+/// archives are left alone.
+fn strip_ooxml(data: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    if archive.index_for_name(CORE_PROPERTIES).is_none() {
+        return Ok(None);
+    }
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::with_capacity(data.len())));
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        if file.name()? == CORE_PROPERTIES {
+            let options =
+                SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+            drop(file);
+            zip.start_file(CORE_PROPERTIES, options)?;
+            zip.write_all(EMPTY_CORE_PROPERTIES.as_bytes())?;
+        } else {
+            zip.raw_copy_file(file)?;
+        }
+    }
+    Ok(Some(zip.finish()?.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jpeg_segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let len = u16::try_from(payload.len() + 2).expect("segment length");
+        let mut segment = vec![0xff, marker];
+        segment.extend_from_slice(&len.to_be_bytes());
+        segment.extend_from_slice(payload);
+        segment
+    }
+
+    /// Little-endian Exif with an orientation and a GPS IFD pointer.
+    fn exif(orientation: u16) -> Vec<u8> {
+        let mut exif = b"Exif\0\0II\x2a\0\x08\0\0\0\x02\0".to_vec();
+        exif.extend_from_slice(&[0x12, 0x01, 0x03, 0x00, 1, 0, 0, 0]);
+        exif.extend_from_slice(&orientation.to_le_bytes());
+        exif.extend_from_slice(&[0, 0]);
+        exif.extend_from_slice(&[0x25, 0x88, 0x04, 0x00, 1, 0, 0, 0, 0x26, 0, 0, 0]);
+        exif.extend_from_slice(&[0; 4]);
+        exif.extend_from_slice(b"GPS 52.5200N 13.4050E");
+        exif
+    }
+
+    fn jpeg(orientation: u16) -> Vec<u8> {
+        let mut jpeg = vec![0xff, 0xd8];
+        jpeg.extend(jpeg_segment(0xe0, b"JFIF\0\x01\x02\0\0\x01\0\x01\0\0"));
+        jpeg.extend(jpeg_segment(0xe1, &exif(orientation)));
+        jpeg.extend(jpeg_segment(
+            0xe1,
+            b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta/>",
+        ));
+        jpeg.extend(jpeg_segment(0xe2, b"ICC_PROFILE\0\x01\x01profile"));
+        jpeg.extend(jpeg_segment(0xed, b"Photoshop 3.0\0IPTC"));
+        jpeg.extend(jpeg_segment(0xfe, b"Camera serial 0451"));
+        jpeg.extend(jpeg_segment(0xdb, &[0; 65]));
+        jpeg.extend(jpeg_segment(0xda, &[1, 1, 0, 0, 63, 0]));
+        jpeg.extend_from_slice(&[0x12, 0xff, 0x00, 0x34, 0xff, 0xd9]);
+        jpeg
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[test]
+    fn jpeg_metadata_is_removed_but_orientation_kept() {
+        let cleaned = strip(&jpeg(6)).expect("strip").expect("supported");
+
+        for removed in [&b"GPS"[..], b"xmpmeta", b"IPTC", b"serial"] {
+            assert!(!contains(&cleaned, removed));
+        }
+        for kept in [
+            &b"JFIF"[..],
+            b"ICC_PROFILE",
+            &[0x12, 0xff, 0x00, 0x34, 0xff, 0xd9],
+        ] {
+            assert!(contains(&cleaned, kept));
+        }
+        let exif = cleaned
+            .windows(6)
+            .position(|window| window == b"Exif\0\0")
+            .expect("orientation exif");
+        assert_eq!(exif_orientation(&cleaned[exif + 6..]), Some(6));
+    }
+
+    #[test]
+    fn upright_jpegs_get_no_exif_at_all() {
+        let cleaned = strip(&jpeg(1)).expect("strip").expect("supported");
+        assert!(!contains(&cleaned, b"Exif"));
+    }
+
+    #[test]
+    fn truncated_jpegs_are_invalid_data() {
+        let jpeg = jpeg(1);
+        let err = strip(&jpeg[..20]).expect_err("truncated");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    fn png_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = u32::try_from(data.len())
+            .expect("chunk length")
+            .to_be_bytes()
+            .to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(data);
+        chunk.extend_from_slice(&[0; 4]);
+        chunk
+    }
+
+    #[test]
+    fn png_text_and_exif_chunks_are_removed() {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend(png_chunk(b"IHDR", &[0; 13]));
+        png.extend(png_chunk(b"tEXt", b"Author\0Jane Doe"));
+        png.extend(png_chunk(
+            b"iTXt",
+            b"XML:com.adobe.xmp\0\0\0\0\0<x:xmpmeta/>",
+        ));
+        png.extend(png_chunk(b"eXIf", b"MM\0*GPS"));
+        png.extend(png_chunk(b"tIME", &[7, 234, 10, 17, 11, 23, 45]));
+        png.extend(png_chunk(b"IDAT", b"pixels"));
+        png.extend(png_chunk(b"IEND", b""));
+
+        let cleaned = strip(&png).expect("strip").expect("supported");
+        let mut expected = PNG_SIGNATURE.to_vec();
+        expected.extend(png_chunk(b"IHDR", &[0; 13]));
+        expected.extend(png_chunk(b"IDAT", b"pixels"));
+        expected.extend(png_chunk(b"IEND", b""));
+        assert_eq!(cleaned, expected);
+    }
+
+    fn webp_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = kind.to_vec();
+        chunk.extend_from_slice(&u32::try_from(data.len()).expect("len").to_le_bytes());
+        chunk.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn webp(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body = chunks.concat();
+        let mut webp = b"RIFF".to_vec();
+        webp.extend_from_slice(&u32::try_from(body.len() + 4).expect("len").to_le_bytes());
+        webp.extend_from_slice(b"WEBP");
+        webp.extend(body);
+        webp
+    }
+
+    #[test]
+    fn webp_exif_and_xmp_chunks_are_removed() {
+        let vp8x = |flags| webp_chunk(b"VP8X", &[flags, 0, 0, 0, 9, 0, 0, 9, 0, 0]);
+        let original = webp(&[
+            vp8x(0x2c),
+            webp_chunk(b"ICCP", b"profile"),
+            webp_chunk(b"VP8L", b"pixels"),
+            webp_chunk(b"EXIF", b"MM\0*GPS"),
+            webp_chunk(b"XMP ", b"<x:xmpmeta/>"),
+        ]);
+
+        let cleaned = strip(&original).expect("strip").expect("supported");
+        assert_eq!(
+            cleaned,
+            webp(&[
+                vp8x(0x20),
+                webp_chunk(b"ICCP", b"profile"),
+                webp_chunk(b"VP8L", b"pixels"),
+            ])
+        );
+    }
+
+    fn pdf() -> Vec<u8> {
+        use lopdf::{dictionary, Object, Stream};
+
+        let mut document = lopdf::Document::with_version("1.7");
+        let pages = document.new_object_id();
+        let page = document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        });
+        document.objects.insert(
+            pages,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page.into()],
+                "Count" => 1,
+            }),
+        );
+        let xmp = document.add_object(Stream::new(
+            dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+            b"<x:xmpmeta><dc:creator>Jane Doe</dc:creator></x:xmpmeta>".to_vec(),
+        ));
+        let catalog = document.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages,
+            "Metadata" => xmp,
+        });
+        let info = document.add_object(dictionary! {
+            "Author" => Object::string_literal("Jane Doe"),
+            "Producer" => Object::string_literal("Clinic Reports 4.2"),
+        });
+        document.trailer.set("Root", catalog);
+        document.trailer.set("Info", info);
+
+        let mut pdf = Vec::new();
+        document.save_to(&mut pdf).expect("save");
+        pdf
+    }
+
+    #[test]
+    fn pdf_info_and_xmp_are_removed() {
+        let original = pdf();
+        assert!(contains(&original, b"Jane Doe"));
+
+        let cleaned = strip(&original).expect("strip").expect("supported");
+        assert!(!contains(&cleaned, b"Jane Doe"));
+        assert!(!contains(&cleaned, b"Clinic Reports"));
+
+        let document = lopdf::Document::load_mem(&cleaned).expect("valid pdf");
+        assert!(document.trailer.get(b"Info").is_err());
+        assert_eq!(document.get_pages().len(), 1);
+    }
+
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, SimpleFileOptions::default())
+                .expect("start file");
+            zip.write_all(content.as_bytes()).expect("write file");
+        }
+        zip.finish().expect("finish").into_inner()
+    }
+
+    fn read(zip: &[u8], name: &str) -> String {
+        let mut archive = ZipArchive::new(Cursor::new(zip)).expect("zip");
+        let mut content = String::new();
+        archive
+            .by_name(name)
+            .expect("file")
+            .read_to_string(&mut content)
+            .expect("read");
+        content
+    }
+
+    #[test]
+    fn ooxml_core_properties_are_reset() {
+        let docx = zip(&[
+            ("[Content_Types].xml", "<Types/>"),
+            (
+                CORE_PROPERTIES,
+                "<cp:coreProperties><dc:creator>Jane Doe</dc:creator></cp:coreProperties>",
+            ),
+            ("word/document.xml", "<w:document>Findings</w:document>"),
+        ]);
+
+        let cleaned = strip(&docx).expect("strip").expect("supported");
+        assert_eq!(read(&cleaned, CORE_PROPERTIES), EMPTY_CORE_PROPERTIES);
+        assert_eq!(
+            read(&cleaned, "word/document.xml"),
+            "<w:document>Findings</w:document>"
+        );
+        assert_eq!(read(&cleaned, "[Content_Types].xml"), "<Types/>");
+    }
+
+    #[test]
+    fn other_formats_are_not_supported() {
+        assert_eq!(strip(b"plain text").expect("strip"), None);
+        assert_eq!(strip(&zip(&[("notes.txt", "hi")])).expect("strip"), None);
+    }
+}
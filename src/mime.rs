@@ -0,0 +1,410 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Number of leading bytes [`sniff`] looks at.
+const HEADER_LEN: usize = 512;
+
+/// MIME type of the file at `path`, from its content or else its extension.
+///
+/// `overrides` maps lowercase extensions without the dot to MIME types and
+/// takes precedence over the built-in extension table.
+pub fn infer_file(path: &Path, overrides: &BTreeMap<String, String>) -> Option<String> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    // Unreadable files are reported by the backend; guess from the name.
+    let _ = std::fs::File::open(path)
+        .and_then(|file| file.take(HEADER_LEN as u64).read_to_end(&mut header));
+    infer(&header, path, overrides)
+}
+
+/// MIME type of in-memory content named `name`, like [`infer_file`].
+pub fn infer(data: &[u8], name: &Path, overrides: &BTreeMap<String, String>) -> Option<String> {
+    let by_extension = || {
+        let extension = name.extension()?.to_str()?.to_ascii_lowercase();
+        overrides
+            .get(&extension)
+            .cloned()
+            .or_else(|| extension_type(&extension).map(Into::into))
+    };
+    match sniff(data) {
+        Some(Magic::Exact(mime)) => Some(mime.into()),
+        Some(Magic::Container(mime)) => by_extension().or_else(|| Some(mime.into())),
+        None => by_extension(),
+    }
+}
+
+/// What the leading bytes of a file say about its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Magic {
+    Exact(&'static str),
+    /// A generic container, such as a ZIP archive, that the extension may
+    /// describe more precisely (`.docx`, `.epub`, ...).
+    Container(&'static str),
+}
+
+fn sniff(data: &[u8]) -> Option<Magic> {
+    use Magic::{Container, Exact};
+
+    const SIGNATURES: &[(&[u8], Magic)] = &[
+        (b"%PDF-", Exact("application/pdf")),
+        (b"\x89PNG\r\n\x1a\n", Exact("image/png")),
+        (b"\xff\xd8\xff", Exact("image/jpeg")),
+        (b"GIF87a", Exact("image/gif")),
+        (b"GIF89a", Exact("image/gif")),
+        (b"II*\x00", Exact("image/tiff")),
+        (b"MM\x00*", Exact("image/tiff")),
+        (b"\x00\x00\x01\x00", Exact("image/vnd.microsoft.icon")),
+        (b"<svg", Exact("image/svg+xml")),
+        (b"ID3", Exact("audio/mpeg")),
+        (b"\xff\xfb", Exact("audio/mpeg")),
+        (b"\xff\xf3", Exact("audio/mpeg")),
+        (b"\xff\xf2", Exact("audio/mpeg")),
+        (b"\xff\xf1", Exact("audio/aac")),
+        (b"\xff\xf9", Exact("audio/aac")),
+        (b"fLaC", Exact("audio/flac")),
+        (b"MThd", Exact("audio/midi")),
+        (b"OggS", Container("audio/ogg")),
+        (b"\x00\x00\x01\xba", Exact("video/mpeg")),
+        (b"\x00\x00\x01\xb3", Exact("video/mpeg")),
+        (b"PK\x03\x04", Container("application/zip")),
+        (b"PK\x05\x06", Container("application/zip")),
+        (b"\x1f\x8b", Exact("application/gzip")),
+        (b"BZh", Exact("application/x-bzip2")),
+        (b"\xfd7zXZ\x00", Exact("application/x-xz")),
+        (b"\x28\xb5\x2f\xfd", Exact("application/zstd")),
+        (b"7z\xbc\xaf\x27\x1c", Exact("application/x-7z-compressed")),
+        (b"Rar!\x1a\x07", Exact("application/vnd.rar")),
+        (
+            b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1",
+            Container("application/x-ole-storage"),
+        ),
+        (b"{\\rtf", Exact("application/rtf")),
+        (b"%!PS", Exact("application/postscript")),
+        (b"<?xml", Container("application/xml")),
+        (b"SQLite format 3\x00", Exact("application/vnd.sqlite3")),
+        (b"\x00asm", Exact("application/wasm")),
+    ];
+
+    if let Some((_, magic)) = SIGNATURES.iter().find(|(sig, _)| data.starts_with(sig)) {
+        return Some(*magic);
+    }
+
+    let at = |offset: usize, sig: &[u8]| data.get(offset..offset + sig.len()) == Some(sig);
+    // "BM" alone would match plain text; the reserved header fields are zero.
+    if at(0, b"BM") && at(6, &[0; 4]) {
+        return Some(Exact("image/bmp"));
+    }
+    if at(0, b"RIFF") {
+        return match data.get(8..12)? {
+            b"WEBP" => Some(Exact("image/webp")),
+            b"WAVE" => Some(Exact("audio/wav")),
+            b"AVI " => Some(Exact("video/x-msvideo")),
+            _ => None,
+        };
+    }
+    if at(4, b"ftyp") {
+        return Some(Exact(match data.get(8..12)? {
+            b"heic" | b"heix" | b"hevc" | b"hevx" => "image/heic",
+            b"mif1" | b"msf1" | b"heif" => "image/heif",
+            b"avif" | b"avis" => "image/avif",
+            b"M4A " | b"M4B " => "audio/mp4",
+            b"M4V " => "video/x-m4v",
+            b"qt  " => "video/quicktime",
+            [b'3', b'g', ..] => "video/3gpp",
+            _ => "video/mp4",
+        }));
+    }
+    if at(0, b"\x1a\x45\xdf\xa3") {
+        let doc_type = data.get(..64).unwrap_or(data);
+        let is_webm = doc_type.windows(4).any(|window| window == b"webm");
+        return Some(Exact(if is_webm {
+            "video/webm"
+        } else {
+            "video/x-matroska"
+        }));
+    }
+    if at(257, b"ustar") {
+        return Some(Exact("application/x-tar"));
+    }
+    None
+}
+
+/// Built-in extension table for common document, image, audio, video and
+/// archive formats.
+fn extension_type(extension: &str) -> Option<&'static str> {
+    Some(match extension {
+        // Documents
+        "pdf" => "application/pdf",
+        "txt" | "text" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "csv" => "text/csv",
+        "tsv" => "text/tab-separated-values",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "rtf" => "application/rtf",
+        "ps" | "eps" => "application/postscript",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+        "odp" => "application/vnd.oasis.opendocument.presentation",
+        "epub" => "application/epub+zip",
+        "pages" => "application/vnd.apple.pages",
+        "numbers" => "application/vnd.apple.numbers",
+        "key" => "application/vnd.apple.keynote",
+        "ics" => "text/calendar",
+        "vcf" => "text/vcard",
+        "eml" => "message/rfc822",
+        // Images
+        "png" => "image/png",
+        "jpg" | "jpeg" | "jpe" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "heic" => "image/heic",
+        "heif" => "image/heif",
+        "avif" => "image/avif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/vnd.microsoft.icon",
+        // Audio
+        "mp3" => "audio/mpeg",
+        "m4a" | "m4b" => "audio/mp4",
+        "aac" => "audio/aac",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "ogg" | "oga" => "audio/ogg",
+        "opus" => "audio/opus",
+        "mid" | "midi" => "audio/midi",
+        // Video
+        "mp4" => "video/mp4",
+        "m4v" => "video/x-m4v",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        "avi" => "video/x-msvideo",
+        "ogv" => "video/ogg",
+        "3gp" => "video/3gpp",
+        "mpeg" | "mpg" => "video/mpeg",
+        // Archives
+        "zip" => "application/zip",
+        "gz" | "tgz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "bz2" => "application/x-bzip2",
+        "xz" => "application/x-xz",
+        "zst" => "application/zstd",
+        "7z" => "application/x-7z-compressed",
+        "rar" => "application/vnd.rar",
+        "apk" => "application/vnd.android.package-archive",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infer_named(data: &[u8], name: &str) -> Option<String> {
+        infer(data, Path::new(name), &BTreeMap::new())
+    }
+
+    fn ftyp(brand: [u8; 4]) -> Vec<u8> {
+        [
+            b"\x00\x00\x00\x18ftyp".as_slice(),
+            &brand,
+            b"\x00\x00\x00\x00",
+        ]
+        .concat()
+    }
+
+    fn riff(format: [u8; 4]) -> Vec<u8> {
+        [b"RIFF\x24\x00\x00\x00".as_slice(), &format].concat()
+    }
+
+    fn tar() -> Vec<u8> {
+        let mut header = vec![0; 512];
+        header[257..262].copy_from_slice(b"ustar");
+        header
+    }
+
+    #[test]
+    fn content_is_recognised_without_an_extension() {
+        let cases: &[(&[u8], &str)] = &[
+            (b"%PDF-1.7\n", "application/pdf"),
+            (b"{\\rtf1\\ansi", "application/rtf"),
+            (b"%!PS-Adobe-3.0", "application/postscript"),
+            (b"\x89PNG\r\n\x1a\n\x00\x00", "image/png"),
+            (b"\xff\xd8\xff\xe0\x00\x10JFIF", "image/jpeg"),
+            (b"GIF89a\x01\x00", "image/gif"),
+            (&riff(*b"WEBP"), "image/webp"),
+            (b"BM\x36\x00\x0c\x00\x00\x00\x00\x00", "image/bmp"),
+            (b"II*\x00\x08\x00", "image/tiff"),
+            (&ftyp(*b"heic"), "image/heic"),
+            (&ftyp(*b"avif"), "image/avif"),
+            (
+                b"<svg xmlns=\"http://www.w3.org/2000/svg\">",
+                "image/svg+xml",
+            ),
+            (b"ID3\x04\x00", "audio/mpeg"),
+            (b"\xff\xfb\x90\x00", "audio/mpeg"),
+            (b"\xff\xf1\x50\x80", "audio/aac"),
+            (b"fLaC\x00\x00", "audio/flac"),
+            (b"OggS\x00\x02", "audio/ogg"),
+            (&riff(*b"WAVE"), "audio/wav"),
+            (&ftyp(*b"M4A "), "audio/mp4"),
+            (b"MThd\x00\x00\x00\x06", "audio/midi"),
+            (&ftyp(*b"isom"), "video/mp4"),
+            (&ftyp(*b"mp42"), "video/mp4"),
+            (&ftyp(*b"qt  "), "video/quicktime"),
+            (&ftyp(*b"3gp5"), "video/3gpp"),
+            (
+                b"\x1a\x45\xdf\xa3\x9f\x42\x86\x81\x01\x42\x82\x84webm",
+                "video/webm",
+            ),
+            (
+                b"\x1a\x45\xdf\xa3\xa3\x42\x86\x81\x01\x42\x82\x88matroska",
+                "video/x-matroska",
+            ),
+            (&riff(*b"AVI "), "video/x-msvideo"),
+            (b"\x00\x00\x01\xba\x44", "video/mpeg"),
+            (b"PK\x03\x04\x14\x00", "application/zip"),
+            (b"\x1f\x8b\x08\x00", "application/gzip"),
+            (&tar(), "application/x-tar"),
+            (b"BZh91AY", "application/x-bzip2"),
+            (b"\xfd7zXZ\x00\x00", "application/x-xz"),
+            (b"7z\xbc\xaf\x27\x1c\x00\x04", "application/x-7z-compressed"),
+            (b"Rar!\x1a\x07\x01\x00", "application/vnd.rar"),
+            (b"SQLite format 3\x00", "application/vnd.sqlite3"),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(
+                infer_named(data, "upload").as_deref(),
+                Some(*expected),
+                "{data:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn extension_is_used_when_content_is_not_recognised() {
+        let cases = [
+            ("notes.txt", Some("text/plain")),
+            ("BMW.txt", Some("text/plain")),
+            ("README.md", Some("text/markdown")),
+            ("table.CSV", Some("text/csv")),
+            ("page.html", Some("text/html")),
+            ("data.json", Some("application/json")),
+            ("invite.ics", Some("text/calendar")),
+            ("contact.vcf", Some("text/vcard")),
+            ("song.opus", Some("audio/opus")),
+            ("clip.mkv", Some("video/x-matroska")),
+            ("backup.tgz", Some("application/gzip")),
+            ("archive.zst", Some("application/zstd")),
+            ("no-extension", None),
+            ("unknown.xyz", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(
+                infer_named(b"plain text", name).as_deref(),
+                expected,
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn extension_refines_generic_containers() {
+        let zip = b"PK\x03\x04\x14\x00";
+        let ole = b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1";
+        let cases: &[(&[u8], &str, &str)] = &[
+            (
+                zip,
+                "letter.docx",
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            ),
+            (
+                zip,
+                "budget.xlsx",
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            ),
+            (
+                zip,
+                "deck.pptx",
+                "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+            ),
+            (zip, "book.epub", "application/epub+zip"),
+            (zip, "draft.odt", "application/vnd.oasis.opendocument.text"),
+            (zip, "photos.zip", "application/zip"),
+            (zip, "download", "application/zip"),
+            (ole, "letter.doc", "application/msword"),
+            (ole, "budget.xls", "application/vnd.ms-excel"),
+            (b"OggS\x00\x02", "movie.ogv", "video/ogg"),
+            (b"<?xml version=\"1.0\"?>", "icon.svg", "image/svg+xml"),
+            (b"<?xml version=\"1.0\"?>", "feed", "application/xml"),
+        ];
+        for (data, name, expected) in cases {
+            assert_eq!(
+                infer_named(data, name).as_deref(),
+                Some(*expected),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn content_wins_over_a_misleading_extension() {
+        assert_eq!(
+            infer_named(b"%PDF-1.4", "scan.jpg").as_deref(),
+            Some("application/pdf")
+        );
+        assert_eq!(
+            infer_named(b"\x89PNG\r\n\x1a\n", "photo.jpeg").as_deref(),
+            Some("image/png")
+        );
+    }
+
+    #[test]
+    fn overrides_replace_the_extension_table() {
+        let overrides = BTreeMap::from([
+            ("md".to_owned(), "text/x-markdown".to_owned()),
+            ("sketch".to_owned(), "application/x-sketch".to_owned()),
+        ]);
+        let infer = |data: &[u8], name: &str| infer(data, Path::new(name), &overrides);
+
+        assert_eq!(
+            infer(b"# Title", "a.md").as_deref(),
+            Some("text/x-markdown")
+        );
+        assert_eq!(
+            infer(b"PK\x03\x04", "design.sketch").as_deref(),
+            Some("application/x-sketch")
+        );
+        assert_eq!(
+            infer(b"%PDF-1.4", "a.md").as_deref(),
+            Some("application/pdf")
+        );
+    }
+
+    #[test]
+    fn files_are_sniffed_from_disk() {
+        let dir = crate::test_util::TempDir::new("mime");
+        let path = dir.0.join("export");
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR").expect("write");
+
+        assert_eq!(
+            infer_file(&path, &BTreeMap::new()).as_deref(),
+            Some("image/png")
+        );
+        assert_eq!(
+            infer_file(&dir.0.join("missing.pdf"), &BTreeMap::new()).as_deref(),
+            Some("application/pdf")
+        );
+    }
+}
@@ -1,10 +1,22 @@
+use std::path::PathBuf;
+
 use serde::de::DeserializeOwned;
 use tauri::{
     plugin::{PluginApi, PluginHandle},
-    AppHandle, Runtime, WebviewWindow,
+    AppHandle, Runtime, Url, WebviewWindow,
 };
 
-use crate::models::{ShareFileOptions, ShareFilePayload, ShareTextOptions, ShareTextPayload};
+use crate::backend::email_share;
+use crate::error::map_invoke_error;
+use crate::ShareExt;
+
+use crate::config::Config;
+use crate::models::{
+    ShareCapabilities, ShareEmailOptions, ShareFileOptions, ShareFilePayload, ShareFilesOptions,
+    ShareFilesPayload, ShareHtmlOptions, ShareHtmlPayload, ShareItem, ShareOptions, SharePayload,
+    ShareTextOptions, ShareTextPayload, ShareUrlOptions, ShareUrlPayload, SharedFile,
+};
+use crate::scope::file_path;
 
 #[cfg(target_os = "android")]
 const PLUGIN_IDENTIFIER: &str = "app.tauri.share";
@@ -14,42 +26,176 @@ tauri::ios_plugin_binding!(init_plugin_share);
 
 #[allow(clippy::needless_pass_by_value)] // signature required by `lib.rs` plugin setup contract
 pub fn init<R: Runtime, C: DeserializeOwned>(
-    _app: &AppHandle<R>,
+    app: &AppHandle<R>,
     api: PluginApi<R, C>,
-) -> crate::Result<ShareKit<R>> {
+    _config: Config,
+) -> crate::Result<NativeBackend<R>> {
     #[cfg(target_os = "android")]
     let handle = api.register_android_plugin(PLUGIN_IDENTIFIER, "SharePlugin")?;
     #[cfg(target_os = "ios")]
     let handle = api.register_ios_plugin(init_plugin_share)?;
 
-    Ok(ShareKit(handle))
+    Ok(NativeBackend {
+        handle,
+        app: app.clone(),
+    })
 }
 
-/// Access to the share APIs.
-pub struct ShareKit<R: Runtime>(PluginHandle<R>);
+/// Built-in backend forwarding to the Android and iOS plugins.
+///
+/// Files are copied into the staging directory before they are handed to
+/// the native plugins, which share the copies as they are.
+pub struct NativeBackend<R: Runtime> {
+    handle: PluginHandle<R>,
+    app: AppHandle<R>,
+}
+
+impl<R: Runtime> NativeBackend<R> {
+    /// On iOS, the share's position anchors the popover iPads show the
+    /// share sheet in.
+    #[allow(clippy::unused_self)] // same signature as the backends that depend on their config
+    pub const fn capabilities(&self) -> ShareCapabilities {
+        ShareCapabilities {
+            text: true,
+            files: true,
+            multiple_files: true,
+            urls: true,
+            reports_result: true,
+            anchors_position: cfg!(target_os = "ios"),
+            reports_dismissal: true,
+        }
+    }
+
+    /// Replaces every file URL in `urls` with the `file://` URL of a staged
+    /// copy, unless it already is one. All copies of one share end up in the
+    /// same directory, copied on a blocking thread.
+    async fn stage_files<'a>(
+        &self,
+        urls: impl IntoIterator<Item = &'a mut String> + Send,
+    ) -> crate::Result<()> {
+        let share = self.app.share();
+        let staging = share.staging();
+        let mut urls: Vec<_> = urls
+            .into_iter()
+            .filter(|url| !staging.contains(&file_path(url)))
+            .collect();
+        if urls.is_empty() {
+            return Ok(());
+        }
+        let sources: Vec<PathBuf> = urls.iter().map(|url| file_path(url)).collect();
+        let copies = share
+            .staging_task(move |staging| staging.stage_copies(sources.iter().map(PathBuf::as_path)))
+            .await?;
+        for (url, copy) in urls.iter_mut().zip(copies) {
+            **url = Url::from_file_path(&copy)
+                .map_or_else(|()| copy.to_string_lossy().into_owned(), String::from);
+        }
+        Ok(())
+    }
 
-impl<R: Runtime> ShareKit<R> {
     pub async fn share_text(
         &self,
         _window: WebviewWindow<R>,
         text: String,
         options: ShareTextOptions,
     ) -> crate::Result<()> {
-        self.0
+        self.handle
             .run_mobile_plugin_async("shareText", ShareTextPayload { text, options })
             .await
-            .map_err(Into::into)
+            .map_err(map_invoke_error)
     }
 
-    pub async fn share_file(
+    pub async fn share_html(
+        &self,
+        _window: WebviewWindow<R>,
+        html: String,
+        text: String,
+        options: ShareHtmlOptions,
+    ) -> crate::Result<()> {
+        self.handle
+            .run_mobile_plugin_async(
+                "shareHtml",
+                ShareHtmlPayload {
+                    html,
+                    text,
+                    options,
+                },
+            )
+            .await
+            .map_err(map_invoke_error)
+    }
+
+    pub async fn share_url(
         &self,
         _window: WebviewWindow<R>,
         url: String,
+        mut options: ShareUrlOptions,
+    ) -> crate::Result<()> {
+        self.stage_files(options.thumbnail.as_mut()).await?;
+        self.handle
+            .run_mobile_plugin_async("shareUrl", ShareUrlPayload { url, options })
+            .await
+            .map_err(map_invoke_error)
+    }
+
+    pub async fn share_file(
+        &self,
+        _window: WebviewWindow<R>,
+        mut url: String,
         options: ShareFileOptions,
     ) -> crate::Result<()> {
-        self.0
+        self.stage_files([&mut url]).await?;
+        self.handle
             .run_mobile_plugin_async("shareFile", ShareFilePayload { url, options })
             .await
-            .map_err(Into::into)
+            .map_err(map_invoke_error)
+    }
+
+    pub async fn share_files(
+        &self,
+        _window: WebviewWindow<R>,
+        mut files: Vec<SharedFile>,
+        options: ShareFilesOptions,
+    ) -> crate::Result<()> {
+        self.stage_files(files.iter_mut().map(|file| &mut file.url))
+            .await?;
+        self.handle
+            .run_mobile_plugin_async("shareFiles", ShareFilesPayload { files, options })
+            .await
+            .map_err(map_invoke_error)
+    }
+
+    /// Opens the share sheet with the body and files, since apps cannot
+    /// be addressed through it.
+    pub async fn share_email(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareEmailOptions,
+    ) -> crate::Result<()> {
+        let (items, options) = email_share(files, options);
+        self.share(window, items, options).await
+    }
+
+    pub async fn share(
+        &self,
+        _window: WebviewWindow<R>,
+        items: Vec<ShareItem>,
+        options: ShareOptions,
+    ) -> crate::Result<()> {
+        let mut items = self
+            .app
+            .share()
+            .staging_task(move |staging| staging.stage_items(items))
+            .await?;
+        self.stage_files(items.iter_mut().filter_map(|item| match item {
+            ShareItem::File { url, .. } => Some(url),
+            _ => None,
+        }))
+        .await?;
+        self.handle
+            .run_mobile_plugin_async("share", SharePayload { items, options })
+            .await
+            .map_err(map_invoke_error)
     }
 }
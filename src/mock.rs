@@ -0,0 +1,949 @@
+//! Recording backend for tests of apps that use the plugin.
+//!
+//! ```no_run
+//! use tauri::test::{mock_builder, mock_context, noop_assets};
+//! use tauri_plugin_sharekit::mock::{MockBackend, MockOutcome, RecordedShare};
+//!
+//! let mock = MockBackend::new();
+//! mock.push_outcome(MockOutcome::Cancelled);
+//!
+//! let app = mock_builder()
+//!     .plugin(tauri_plugin_sharekit::Builder::new().backend(mock.clone()).build())
+//!     .build(mock_context(noop_assets()))
+//!     .expect("failed to build app");
+//!
+//! // ... drive the app ...
+//!
+//! assert!(matches!(
+//!     mock.requests().as_slice(),
+//!     [RecordedShare::Text { text, .. }] if text == "Hello"
+//! ));
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tauri::{Runtime, WebviewWindow};
+
+use crate::backend::{ShareBackend, ShareFuture};
+use crate::models::{
+    ShareCapabilities, ShareEmailOptions, ShareFileOptions, ShareFilesOptions, ShareHtmlOptions,
+    ShareItem, ShareOptions, ShareTextOptions, ShareUrlOptions, SharedFile,
+};
+use crate::Error;
+
+/// A share request received by a [`MockBackend`], with every option it
+/// was called with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedShare {
+    Text {
+        /// Label of the window the share was presented over.
+        window: String,
+        text: String,
+        options: ShareTextOptions,
+    },
+    Html {
+        window: String,
+        html: String,
+        text: String,
+        options: ShareHtmlOptions,
+    },
+    Url {
+        window: String,
+        url: String,
+        options: ShareUrlOptions,
+    },
+    File {
+        window: String,
+        url: String,
+        options: ShareFileOptions,
+    },
+    Files {
+        window: String,
+        files: Vec<SharedFile>,
+        options: ShareFilesOptions,
+    },
+    Email {
+        window: String,
+        files: Vec<SharedFile>,
+        options: ShareEmailOptions,
+    },
+    Items {
+        window: String,
+        items: Vec<ShareItem>,
+        options: ShareOptions,
+    },
+}
+
+/// Result a [`MockBackend`] reports for a request.
+#[derive(Debug)]
+pub enum MockOutcome {
+    Success,
+    /// Fails with [`Error::ShareCancelled`], as if the user dismissed the
+    /// share sheet.
+    Cancelled,
+    Error(Error),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    requests: Vec<RecordedShare>,
+    outcomes: VecDeque<MockOutcome>,
+    capabilities: Option<ShareCapabilities>,
+}
+
+/// [`ShareBackend`] that records requests instead of showing a share UI.
+///
+/// Clones share their state, so keep one clone in the test and register
+/// another with [`Builder::backend`](crate::Builder::backend). Requests
+/// succeed unless an outcome was queued with
+/// [`push_outcome`](Self::push_outcome). It supports everything unless
+/// other capabilities were set with
+/// [`set_capabilities`](Self::set_capabilities).
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockBackend {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the outcome of the next request that has no outcome yet.
+    pub fn push_outcome(&self, outcome: MockOutcome) {
+        self.state().outcomes.push_back(outcome);
+    }
+
+    /// Reports `capabilities`, e.g. to test how an app handles a platform
+    /// that cannot share files.
+    pub fn set_capabilities(&self, capabilities: ShareCapabilities) {
+        self.state().capabilities = Some(capabilities);
+    }
+
+    /// Every request received so far, oldest first.
+    #[must_use]
+    pub fn requests(&self) -> Vec<RecordedShare> {
+        self.state().requests.clone()
+    }
+
+    /// Forgets recorded requests, queued outcomes and set capabilities.
+    pub fn clear(&self) {
+        *self.state() = State::default();
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panicking test must not hide the requests from other assertions.
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn record(&self, request: RecordedShare) -> ShareFuture<'static> {
+        let outcome = {
+            let mut state = self.state();
+            state.requests.push(request);
+            state.outcomes.pop_front()
+        };
+        let result = match outcome {
+            None | Some(MockOutcome::Success) => Ok(()),
+            Some(MockOutcome::Cancelled) => Err(Error::ShareCancelled),
+            Some(MockOutcome::Error(err)) => Err(err),
+        };
+        Box::pin(async move { result })
+    }
+}
+
+impl<R: Runtime> ShareBackend<R> for MockBackend {
+    fn share(
+        &self,
+        window: WebviewWindow<R>,
+        items: Vec<ShareItem>,
+        options: ShareOptions,
+    ) -> ShareFuture<'_> {
+        self.record(RecordedShare::Items {
+            window: window.label().to_owned(),
+            items,
+            options,
+        })
+    }
+
+    fn share_text(
+        &self,
+        window: WebviewWindow<R>,
+        text: String,
+        options: ShareTextOptions,
+    ) -> ShareFuture<'_> {
+        self.record(RecordedShare::Text {
+            window: window.label().to_owned(),
+            text,
+            options,
+        })
+    }
+
+    fn share_html(
+        &self,
+        window: WebviewWindow<R>,
+        html: String,
+        text: String,
+        options: ShareHtmlOptions,
+    ) -> ShareFuture<'_> {
+        self.record(RecordedShare::Html {
+            window: window.label().to_owned(),
+            html,
+            text,
+            options,
+        })
+    }
+
+    fn share_url(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        options: ShareUrlOptions,
+    ) -> ShareFuture<'_> {
+        self.record(RecordedShare::Url {
+            window: window.label().to_owned(),
+            url,
+            options,
+        })
+    }
+
+    fn share_file(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        options: ShareFileOptions,
+    ) -> ShareFuture<'_> {
+        self.record(RecordedShare::File {
+            window: window.label().to_owned(),
+            url,
+            options,
+        })
+    }
+
+    fn share_files(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareFilesOptions,
+    ) -> ShareFuture<'_> {
+        self.record(RecordedShare::Files {
+            window: window.label().to_owned(),
+            files,
+            options,
+        })
+    }
+
+    fn share_email(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareEmailOptions,
+    ) -> ShareFuture<'_> {
+        self.record(RecordedShare::Email {
+            window: window.label().to_owned(),
+            files,
+            options,
+        })
+    }
+
+    fn capabilities(&self) -> ShareCapabilities {
+        self.state().capabilities.unwrap_or(ShareCapabilities {
+            text: true,
+            files: true,
+            multiple_files: true,
+            urls: true,
+            reports_result: true,
+            anchors_position: true,
+            reports_dismissal: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+    use tauri::{App, WebviewUrl, WebviewWindowBuilder};
+
+    use super::*;
+    use crate::models::{RectEdge, SharePosition};
+    use crate::ShareExt;
+
+    fn app(mock: &MockBackend) -> App<MockRuntime> {
+        app_with_config(mock, serde_json::Value::Null)
+    }
+
+    /// App with `config` as its `plugins.sharekit` configuration.
+    fn app_with_config(mock: &MockBackend, config: serde_json::Value) -> App<MockRuntime> {
+        let mut context = mock_context(noop_assets());
+        context
+            .config_mut()
+            .plugins
+            .0
+            .insert("sharekit".into(), config);
+        mock_builder()
+            .plugin(crate::Builder::new().backend(mock.clone()).build())
+            .build(context)
+            .expect("build app")
+    }
+
+    fn window(app: &App<MockRuntime>) -> WebviewWindow<MockRuntime> {
+        WebviewWindowBuilder::new(app, "main", WebviewUrl::default())
+            .build()
+            .expect("mock window")
+    }
+
+    #[tokio::test]
+    async fn records_requests_with_their_options() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let window = window(&app);
+
+        app.share()
+            .share_text(
+                window.clone(),
+                "Hello".into(),
+                ShareTextOptions {
+                    mime_type: Some("text/plain".into()),
+                    title: Some("Greeting".into()),
+                    subject: Some("Hi".into()),
+                    position: Some(SharePosition {
+                        x: 10.0,
+                        y: 20.0,
+                        preferred_edge: Some(RectEdge::Top),
+                    }),
+                },
+            )
+            .await
+            .expect("share text");
+        window
+            .share_request()
+            .file("/tmp/report.pdf")
+            .mime("application/pdf")
+            .title("Report")
+            .send()
+            .await
+            .expect("share file");
+
+        assert_eq!(
+            mock.requests(),
+            vec![
+                RecordedShare::Text {
+                    window: "main".into(),
+                    text: "Hello".into(),
+                    options: ShareTextOptions {
+                        mime_type: Some("text/plain".into()),
+                        title: Some("Greeting".into()),
+                        subject: Some("Hi".into()),
+                        position: Some(SharePosition {
+                            x: 10.0,
+                            y: 20.0,
+                            preferred_edge: Some(RectEdge::Top),
+                        }),
+                    },
+                },
+                RecordedShare::File {
+                    window: "main".into(),
+                    url: "/tmp/report.pdf".into(),
+                    options: ShareFileOptions {
+                        mime_type: Some("application/pdf".into()),
+                        title: Some("Report".into()),
+                        file_name: None,
+                        position: None,
+                        archive: None,
+                        image: None,
+                        strip_metadata: None,
+                        encryption: None,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn scripted_outcomes_are_used_in_order() {
+        let mock = MockBackend::new();
+        mock.push_outcome(MockOutcome::Cancelled);
+        mock.push_outcome(MockOutcome::Error(Error::UnsupportedPlatform));
+        let app = app(&mock);
+        let window = window(&app);
+        let share = || window.share_request().text("Hello").send();
+
+        assert!(matches!(share().await, Err(Error::ShareCancelled)));
+        assert!(matches!(share().await, Err(Error::UnsupportedPlatform)));
+        assert!(share().await.is_ok());
+        assert_eq!(mock.requests().len(), 3);
+
+        mock.clear();
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn urls_are_normalized_before_they_are_shared() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let window = window(&app);
+        let options = ShareUrlOptions {
+            title: Some("Example".into()),
+            ..ShareUrlOptions::default()
+        };
+
+        app.share()
+            .share_url(
+                window.clone(),
+                "HTTPS://Example.com/a/../b",
+                options.clone(),
+            )
+            .await
+            .expect("share url");
+        let err = app
+            .share()
+            .share_url(window, "javascript:alert(1)", options.clone())
+            .await
+            .expect_err("local URL");
+
+        assert!(matches!(err, Error::InvalidInput(_)));
+        assert_eq!(
+            mock.requests(),
+            vec![RecordedShare::Url {
+                window: "main".into(),
+                url: "https://example.com/b".into(),
+                options,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn urls_without_a_title_get_the_default_title() {
+        let mock = MockBackend::new();
+        let app = app_with_config(
+            &mock,
+            serde_json::json!({ "defaultTitle": "Shared from Demo" }),
+        );
+
+        app.share()
+            .share_url(
+                window(&app),
+                "https://example.com",
+                ShareUrlOptions::default(),
+            )
+            .await
+            .expect("share url");
+
+        let requests = mock.requests();
+        let [RecordedShare::Url { options, .. }] = requests.as_slice() else {
+            panic!("expected a single url share");
+        };
+        assert_eq!(options.title.as_deref(), Some("Shared from Demo"));
+    }
+
+    #[tokio::test]
+    async fn html_is_sanitized_and_gets_a_plain_text_alternative() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let window = window(&app);
+
+        app.share()
+            .share_html(
+                window.clone(),
+                "<p>Buy <b>milk</b></p><script>alert(1)</script>",
+                None,
+                ShareHtmlOptions::default(),
+            )
+            .await
+            .expect("share html");
+        app.share()
+            .share_html(
+                window,
+                "<p>Buy <b>milk</b></p>",
+                Some("Buy milk".into()),
+                ShareHtmlOptions::default(),
+            )
+            .await
+            .expect("share html with text");
+
+        assert_eq!(
+            mock.requests(),
+            vec![
+                RecordedShare::Html {
+                    window: "main".into(),
+                    html: "<p>Buy <b>milk</b></p>".into(),
+                    text: "Buy **milk**".into(),
+                    options: ShareHtmlOptions::default(),
+                },
+                RecordedShare::Html {
+                    window: "main".into(),
+                    html: "<p>Buy <b>milk</b></p>".into(),
+                    text: "Buy milk".into(),
+                    options: ShareHtmlOptions::default(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn html_in_mixed_shares_is_sanitized() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let items = vec![ShareItem::Html {
+            html: "<p>Buy <b>milk</b></p><script>alert(1)</script>".into(),
+        }];
+
+        app.share()
+            .share(window(&app), items, ShareOptions::default())
+            .await
+            .expect("share");
+
+        let requests = mock.requests();
+        let [RecordedShare::Items { items, .. }] = requests.as_slice() else {
+            panic!("expected a single share");
+        };
+        assert_eq!(
+            items,
+            &[ShareItem::Html {
+                html: "<p>Buy <b>milk</b></p>".into()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_mixed_shares_are_rejected() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+
+        let err = app
+            .share()
+            .share(window(&app), Vec::new(), ShareOptions::default())
+            .await
+            .expect_err("no items");
+        assert!(matches!(err, Error::InvalidInput(_)));
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn local_links_in_mixed_shares_are_rejected() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let window = window(&app);
+        let items = vec![
+            ShareItem::Text {
+                text: "Keys".into(),
+            },
+            ShareItem::Url {
+                url: "file:///home/user/.ssh/id_rsa".into(),
+            },
+        ];
+
+        assert!(!app.share().can_share(&items));
+        let err = app
+            .share()
+            .share(window, items, ShareOptions::default())
+            .await
+            .expect_err("local link");
+        assert!(matches!(err, Error::InvalidInput(_)));
+        assert!(mock.requests().is_empty());
+    }
+
+    #[test]
+    fn can_share_follows_the_backend_capabilities() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let files = [
+            ShareItem::File {
+                url: "/tmp/a.pdf".into(),
+                mime_type: None,
+            },
+            ShareItem::File {
+                url: "/tmp/b.pdf".into(),
+                mime_type: None,
+            },
+        ];
+        assert!(app.share().can_share(&files));
+
+        mock.set_capabilities(ShareCapabilities {
+            text: true,
+            files: true,
+            ..ShareCapabilities::default()
+        });
+        assert!(!app.share().capabilities().multiple_files);
+        assert!(!app.share().can_share(&files));
+        assert!(app.share().can_share(&files[..1]));
+    }
+
+    #[tokio::test]
+    async fn empty_file_lists_are_rejected() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+
+        let err = app
+            .share()
+            .share_files(window(&app), Vec::new(), ShareFilesOptions::default())
+            .await
+            .expect_err("no files");
+        assert!(matches!(err, Error::InvalidInput(_)));
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn file_urls_reach_backends_as_paths() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let dir = crate::test_util::TempDir::new("mock");
+        let source = dir.file("notes.txt");
+        let url = tauri::Url::from_file_path(&source).expect("file url");
+
+        app.share()
+            .share_file(window(&app), url.into(), ShareFileOptions::default())
+            .await
+            .expect("share file");
+
+        let requests = mock.requests();
+        let [RecordedShare::File { url, .. }] = requests.as_slice() else {
+            panic!("expected a single file share");
+        };
+        assert_eq!(std::path::Path::new(url), source);
+    }
+
+    #[tokio::test]
+    async fn backends_receive_a_renamed_copy() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let dir = crate::test_util::TempDir::new("mock");
+        let source = dir.0.join("a91f3c.bin");
+        std::fs::write(&source, b"%PDF-1.7\n").expect("write source");
+
+        app.share()
+            .share_file(
+                window(&app),
+                source.to_string_lossy().into_owned(),
+                ShareFileOptions {
+                    file_name: Some("Invoice-2026-10.pdf".into()),
+                    ..ShareFileOptions::default()
+                },
+            )
+            .await
+            .expect("share file");
+
+        let requests = mock.requests();
+        let [RecordedShare::File { url, options, .. }] = requests.as_slice() else {
+            panic!("expected a single file share");
+        };
+        let copy = std::path::Path::new(url);
+        assert!(copy.starts_with(app.share().staging().root()));
+        assert_eq!(copy.file_name(), Some("Invoice-2026-10.pdf".as_ref()));
+        assert_eq!(options.file_name, None);
+        assert_eq!(options.mime_type.as_deref(), Some("application/pdf"));
+        assert!(source.exists());
+    }
+
+    #[tokio::test]
+    async fn emails_are_checked_and_typed_before_they_are_shared() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let window = window(&app);
+        let dir = crate::test_util::TempDir::new("mock");
+        let report = dir.0.join("report");
+        std::fs::write(&report, b"%PDF-1.7\n").expect("write report");
+        let files = vec![SharedFile {
+            url: report.to_string_lossy().into_owned(),
+            mime_type: None,
+        }];
+        let options = ShareEmailOptions {
+            to: vec!["ana@example.com".into()],
+            subject: Some("Report".into()),
+            ..ShareEmailOptions::default()
+        };
+
+        let err = app
+            .share()
+            .share_email(
+                window.clone(),
+                files.clone(),
+                ShareEmailOptions {
+                    cc: vec!["eve@example.com\r\nBcc: x@example.com".into()],
+                    ..options.clone()
+                },
+            )
+            .await
+            .expect_err("header injection");
+        assert!(matches!(err, Error::InvalidInput(_)));
+        let err = app
+            .share()
+            .share_email(
+                window.clone(),
+                vec![SharedFile {
+                    url: dir.0.to_string_lossy().into_owned(),
+                    mime_type: None,
+                }],
+                options.clone(),
+            )
+            .await
+            .expect_err("directory");
+        assert!(matches!(err, Error::InvalidInput(_)));
+        app.share()
+            .share_email(window, files, options.clone())
+            .await
+            .expect("share email");
+
+        assert_eq!(
+            mock.requests(),
+            vec![RecordedShare::Email {
+                window: "main".into(),
+                files: vec![SharedFile {
+                    url: report.to_string_lossy().into_owned(),
+                    mime_type: Some("application/pdf".into()),
+                }],
+                options,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn stripped_shares_leave_the_source_untouched() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let dir = crate::test_util::TempDir::new("mock");
+        let source = dir.0.join("scan.png");
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        for (kind, data) in [(&b"IHDR"[..], &[0; 13][..]), (b"tEXt", b"Author\0Jane Doe")] {
+            png.extend_from_slice(&u32::try_from(data.len()).expect("len").to_be_bytes());
+            png.extend_from_slice(kind);
+            png.extend_from_slice(data);
+            png.extend_from_slice(&[0; 4]);
+        }
+        std::fs::write(&source, &png).expect("write source");
+
+        app.share()
+            .share_file(
+                window(&app),
+                source.to_string_lossy().into_owned(),
+                ShareFileOptions {
+                    strip_metadata: Some(true),
+                    ..ShareFileOptions::default()
+                },
+            )
+            .await
+            .expect("share file");
+
+        let requests = mock.requests();
+        let [RecordedShare::File { url, options, .. }] = requests.as_slice() else {
+            panic!("expected a single file share");
+        };
+        let copy = std::path::Path::new(url);
+        assert!(copy.starts_with(app.share().staging().root()));
+        assert_eq!(copy.file_name(), Some("scan.png".as_ref()));
+        assert_eq!(options.strip_metadata, None);
+        assert_eq!(options.mime_type.as_deref(), Some("image/png"));
+        assert_eq!(std::fs::read(copy).expect("read copy"), &png[..33]);
+        assert_eq!(std::fs::read(&source).expect("read source"), png);
+    }
+
+    #[tokio::test]
+    async fn converted_images_get_their_new_name_and_type() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgba8(64, 32)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .expect("encode png");
+
+        app.share()
+            .share_data(
+                window(&app),
+                &png,
+                crate::ShareDataOptions {
+                    name: "chart.png".into(),
+                    mime_type: Some("image/png".into()),
+                    image: Some(crate::ImageOptions {
+                        max_dimension: Some(16),
+                        format: Some(crate::ImageFormat::Jpeg),
+                        ..crate::ImageOptions::default()
+                    }),
+                    ..crate::ShareDataOptions::default()
+                },
+            )
+            .await
+            .expect("share data");
+
+        let requests = mock.requests();
+        let [RecordedShare::File { url, options, .. }] = requests.as_slice() else {
+            panic!("expected a single file share");
+        };
+        assert!(url.ends_with("chart.jpg"));
+        assert_eq!(options.mime_type.as_deref(), Some("image/jpeg"));
+        let image = image::open(url).expect("decode staged image");
+        assert_eq!((image.width(), image.height()), (16, 8));
+    }
+
+    #[tokio::test]
+    async fn images_are_encoded_and_staged() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let window = window(&app);
+        let options = crate::ShareImageOptions {
+            name: Some("Screenshot".into()),
+            format: Some(crate::ImageFormat::Jpeg),
+            ..crate::ShareImageOptions::default()
+        };
+
+        app.share()
+            .share_image(
+                window.clone(),
+                tauri::image::Image::new_owned([0, 128, 255, 255].repeat(6), 3, 2),
+                options.clone(),
+            )
+            .await
+            .expect("share image");
+        let requests = mock.requests();
+        let [RecordedShare::File {
+            url,
+            options: shared,
+            ..
+        }] = requests.as_slice()
+        else {
+            panic!("expected a single file share");
+        };
+        assert!(url.ends_with("Screenshot.jpg"));
+        assert_eq!(shared.mime_type.as_deref(), Some("image/jpeg"));
+        let image = image::open(url).expect("decode staged image");
+        assert_eq!((image.width(), image.height()), (3, 2));
+
+        let result = app
+            .share()
+            .share_image(
+                window,
+                tauri::image::Image::new_owned(vec![0; 20], 3, 2),
+                options,
+            )
+            .await;
+        assert!(
+            matches!(result, Err(Error::InvalidInput(message)) if message.contains("24 bytes"))
+        );
+    }
+
+    #[tokio::test]
+    async fn directories_are_shared_as_archives() {
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let dir = crate::test_util::TempDir::new("mock");
+        dir.file("photos/a.jpg");
+
+        app.share()
+            .share_files(
+                window(&app),
+                vec![SharedFile {
+                    url: dir.0.join("photos").to_string_lossy().into_owned(),
+                    mime_type: None,
+                }],
+                ShareFilesOptions {
+                    title: Some("Photos".into()),
+                    ..ShareFilesOptions::default()
+                },
+            )
+            .await
+            .expect("share directory");
+
+        let requests = mock.requests();
+        let [RecordedShare::File { url, options, .. }] = requests.as_slice() else {
+            panic!("expected a single file share");
+        };
+        let archive = std::path::Path::new(url);
+        assert!(archive.starts_with(app.share().staging().root()));
+        assert_eq!(archive.file_name(), Some("photos.zip".as_ref()));
+        assert_eq!(options.mime_type.as_deref(), Some("application/zip"));
+        assert_eq!(options.title.as_deref(), Some("Photos"));
+        assert_eq!(options.archive, None);
+    }
+
+    #[tokio::test]
+    async fn encrypted_shares_only_stage_the_encrypted_file() {
+        use std::io::Read;
+
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let identity = age::x25519::Identity::generate();
+        let encryption =
+            crate::Encryption::age([identity.to_public().to_string()]).expect("recipient");
+
+        app.share()
+            .share_data(
+                window(&app),
+                b"%PDF-1.7 patient record",
+                crate::ShareDataOptions {
+                    name: "record.pdf".into(),
+                    mime_type: Some("application/pdf".into()),
+                    encryption: Some(encryption),
+                    ..crate::ShareDataOptions::default()
+                },
+            )
+            .await
+            .expect("share data");
+
+        let requests = mock.requests();
+        let [RecordedShare::File { url, options, .. }] = requests.as_slice() else {
+            panic!("expected a single file share");
+        };
+        let staged = std::path::Path::new(url);
+        assert_eq!(staged.file_name(), Some("record.pdf.age".as_ref()));
+        assert_eq!(
+            std::fs::read_dir(staged.parent().expect("share dir"))
+                .expect("read share dir")
+                .count(),
+            1
+        );
+        assert_eq!(
+            options.mime_type.as_deref(),
+            Some("application/octet-stream")
+        );
+
+        let mut data = Vec::new();
+        age::Decryptor::new(std::fs::File::open(staged).expect("open"))
+            .expect("age header")
+            .decrypt(std::iter::once(&identity as &dyn age::Identity))
+            .expect("decrypt")
+            .read_to_end(&mut data)
+            .expect("read");
+        assert_eq!(data, b"%PDF-1.7 patient record");
+    }
+
+    #[tokio::test]
+    async fn encrypted_zip_archives_hold_encrypted_files() {
+        use std::io::Read;
+
+        let mock = MockBackend::new();
+        let app = app(&mock);
+        let dir = crate::test_util::TempDir::new("mock");
+        dir.file("records/a.txt");
+
+        app.share()
+            .share_file(
+                window(&app),
+                dir.0.join("records").to_string_lossy().into_owned(),
+                ShareFileOptions {
+                    encryption: Some(
+                        crate::Encryption::zip_aes("correct horse").expect("password"),
+                    ),
+                    ..ShareFileOptions::default()
+                },
+            )
+            .await
+            .expect("share directory");
+
+        let requests = mock.requests();
+        let [RecordedShare::File { url, .. }] = requests.as_slice() else {
+            panic!("expected a single file share");
+        };
+        assert!(url.ends_with("records.zip"));
+        let mut zip =
+            zip::ZipArchive::new(std::fs::File::open(url).expect("open")).expect("read zip");
+        assert!(zip.by_name("records/a.txt").is_err());
+        let mut data = String::new();
+        zip.by_name_decrypt("records/a.txt", b"correct horse")
+            .expect("decrypt")
+            .read_to_string(&mut data)
+            .expect("read");
+        assert_eq!(data, "data");
+    }
+}
@@ -1,6 +1,11 @@
+use std::fmt;
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+use crate::encryption::Encryption;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RectEdge {
     Top,
@@ -9,7 +14,7 @@ pub enum RectEdge {
     Right,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SharePosition {
     pub x: f64,
@@ -19,11 +24,16 @@ pub struct SharePosition {
     pub preferred_edge: Option<RectEdge>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShareTextOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Subject line when the text is sent by email.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
     /// Position for the share sheet (iPad/macOS only)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub position: Option<SharePosition>,
@@ -36,16 +46,60 @@ pub struct ShareTextPayload {
     pub options: ShareTextOptions,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// Options for sharing formatted text.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareHtmlOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Subject line when the text is sent by email.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Position for the share sheet (iPad/macOS only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<SharePosition>,
+}
+
+#[derive(Serialize)]
+pub struct ShareHtmlPayload {
+    pub html: String,
+    /// Plain-text alternative for apps that cannot show HTML.
+    pub text: String,
+    #[serde(flatten)]
+    pub options: ShareHtmlOptions,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ShareFileOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mime_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    /// Name recipients see instead of the source file's name. The file is
+    /// shared as a staged copy with this name, made safe for every platform.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
     /// Position for the share sheet (iPad/macOS only)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub position: Option<SharePosition>,
+    /// Packs the file into an archive and shares that instead. Directories
+    /// are always archived, with default options if this is `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<ArchiveOptions>,
+    /// Shares a resized or converted copy of an image. Not applied to
+    /// archives.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageOptions>,
+    /// Shares a copy without privacy metadata, such as camera details and
+    /// locations in photos or authors in documents. `None` uses the
+    /// configured `stripMetadata`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strip_metadata: Option<bool>,
+    /// Shares an encrypted copy of the file, or of its archive. Only Rust
+    /// code can set this.
+    #[serde(skip)]
+    pub encryption: Option<Encryption>,
 }
 
 #[derive(Serialize)]
@@ -54,3 +108,475 @@ pub struct ShareFilePayload {
     #[serde(flatten)]
     pub options: ShareFileOptions,
 }
+
+/// Options for sharing a link.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareUrlOptions {
+    /// Title of the linked page, shown in link previews.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Summary of the linked page, shown by Windows and put into emails on
+    /// Linux.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Path or `file://` URL of an image shown in link previews.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    /// Position for the share sheet (iPad/macOS only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<SharePosition>,
+}
+
+#[derive(Serialize)]
+pub struct ShareUrlPayload {
+    pub url: String,
+    #[serde(flatten)]
+    pub options: ShareUrlOptions,
+}
+
+/// Options for sharing files by email.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareEmailOptions {
+    /// Recipients, as `someone@example.com` or `Name <someone@example.com>`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub to: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cc: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+/// A single file in a multi-file share.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedFile {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareFilesOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Position for the share sheet (iPad/macOS only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<SharePosition>,
+    /// Packs all files into one archive and shares that instead. Groups
+    /// containing a directory are always archived.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<ArchiveOptions>,
+    /// Shares copies without privacy metadata. `None` uses the configured
+    /// `stripMetadata`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strip_metadata: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct ShareFilesPayload {
+    pub files: Vec<SharedFile>,
+    #[serde(flatten)]
+    pub options: ShareFilesOptions,
+}
+
+/// Container format of a generated archive.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ArchiveFormat {
+    #[default]
+    Zip,
+    TarGz,
+}
+
+/// What to do with symbolic links found inside an archived directory. Files
+/// and directories passed in directly are always read through their links.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SymlinkPolicy {
+    /// Leaves links out of the archive.
+    #[default]
+    Skip,
+    /// Archives the link targets as if they were regular files and
+    /// directories. Links that loop back to a parent are not descended into.
+    Follow,
+}
+
+/// Options for packing shared files into an archive.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveOptions {
+    #[serde(default)]
+    pub format: ArchiveFormat,
+    /// File name of the archive; the format's extension is added if it is
+    /// missing. Defaults to the name of a lone shared file or directory, or
+    /// `Archive`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub symlinks: SymlinkPolicy,
+    /// Called while the archive is written.
+    #[serde(skip)]
+    pub progress: Option<ProgressHandler>,
+}
+
+/// How far writing an archive has come.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveProgress {
+    pub files_done: u64,
+    pub files_total: u64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+/// Callback receiving [`ArchiveProgress`] updates. It is called on the
+/// thread writing the archive, so it should return quickly.
+#[derive(Clone)]
+pub struct ProgressHandler(Arc<dyn Fn(ArchiveProgress) + Send + Sync>);
+
+impl ProgressHandler {
+    pub fn new(handler: impl Fn(ArchiveProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    pub(crate) fn report(&self, progress: ArchiveProgress) {
+        (self.0)(progress);
+    }
+}
+
+impl fmt::Debug for ProgressHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressHandler").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ProgressHandler {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Encoding of a processed image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+/// Options for re-encoding an image before it is shared. Images that
+/// already match them are shared as they are; SVG images are always
+/// rasterised.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageOptions {
+    /// Largest width or height in pixels. Larger images are scaled down,
+    /// keeping their aspect ratio; SVG images are rasterised to this size.
+    /// At most 65535.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_dimension: Option<u32>,
+    /// JPEG and WebP quality from 1 to 100, 85 by default. Setting it
+    /// re-encodes JPEG and WebP images even if nothing else changes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+    /// Format to convert to. Defaults to the image's own format, and PNG for
+    /// SVG and GIF images.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<ImageFormat>,
+}
+
+/// Options for sharing an image held in memory, such as a screenshot.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareImageOptions {
+    /// File name the receiving app sees, `Image` by default. The extension
+    /// of the format is added.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// PNG by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<ImageFormat>,
+    /// JPEG and WebP quality from 1 to 100, 85 by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Position for the share sheet (iPad/macOS only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<SharePosition>,
+}
+
+/// Options for sharing in-memory content as a file.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareDataOptions {
+    /// File name the receiving app sees, e.g. `export.png`. Only the final
+    /// path component is used.
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Position for the share sheet (iPad/macOS only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<SharePosition>,
+    /// Shares a resized or converted copy of an image.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageOptions>,
+    /// Shares the content without privacy metadata. `None` uses the
+    /// configured `stripMetadata`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strip_metadata: Option<bool>,
+    /// Shares the content encrypted instead. Only Rust code can set this.
+    #[serde(skip)]
+    pub encryption: Option<Encryption>,
+}
+
+/// A single piece of content in a mixed-content share, modelled after the
+/// Web Share API's `ShareData`. More kinds of content may be added later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ShareItem {
+    Text {
+        text: String,
+    },
+    Url {
+        url: String,
+    },
+    File {
+        url: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+    },
+    Html {
+        html: String,
+    },
+    /// In-memory content, written to a temporary file before it is shared.
+    Bytes {
+        data: Vec<u8>,
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+    },
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Position for the share sheet (iPad/macOS only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<SharePosition>,
+    /// Shares files and bytes without privacy metadata. `None` uses the
+    /// configured `stripMetadata`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strip_metadata: Option<bool>,
+}
+
+/// What a share backend supports, so apps can hide sharing where it does
+/// not work instead of failing when it is used.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ShareCapabilities {
+    /// Text and HTML.
+    pub text: bool,
+    /// Files and in-memory content.
+    pub files: bool,
+    /// Several files in one share.
+    pub multiple_files: bool,
+    pub urls: bool,
+    /// A share only succeeds once the user picked where the content goes,
+    /// rather than as soon as the share UI opened.
+    pub reports_result: bool,
+    /// The share UI is anchored at the share's `position`.
+    pub anchors_position: bool,
+    /// Dismissing the share UI fails the share with
+    /// [`Error::ShareCancelled`](crate::Error::ShareCancelled).
+    pub reports_dismissal: bool,
+}
+
+impl ShareCapabilities {
+    /// Whether a backend with these capabilities can share `items`.
+    #[must_use]
+    pub fn supports(&self, items: &[ShareItem]) -> bool {
+        let files = items
+            .iter()
+            .filter(|item| matches!(item, ShareItem::File { .. } | ShareItem::Bytes { .. }))
+            .count();
+        !items.is_empty()
+            && (files <= 1 || self.multiple_files)
+            && items.iter().all(|item| match item {
+                ShareItem::Text { .. } | ShareItem::Html { .. } => self.text,
+                ShareItem::Url { .. } => self.urls,
+                ShareItem::File { .. } | ShareItem::Bytes { .. } => self.files,
+            })
+    }
+}
+
+#[derive(Serialize)]
+pub struct SharePayload {
+    pub items: Vec<ShareItem>,
+    #[serde(flatten)]
+    pub options: ShareOptions,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(item: &ShareItem) -> ShareItem {
+        let json = serde_json::to_string(item).expect("serialize");
+        serde_json::from_str(&json).expect("deserialize")
+    }
+
+    #[test]
+    fn share_items_round_trip() {
+        let items = [
+            ShareItem::Text {
+                text: "Look at this".into(),
+            },
+            ShareItem::Url {
+                url: "https://tauri.app".into(),
+            },
+            ShareItem::File {
+                url: "/tmp/report.pdf".into(),
+                mime_type: Some("application/pdf".into()),
+            },
+            ShareItem::File {
+                url: "/tmp/notes.txt".into(),
+                mime_type: None,
+            },
+            ShareItem::Html {
+                html: "<b>bold</b>".into(),
+            },
+            ShareItem::Bytes {
+                data: vec![0, 1, 2, 255],
+                name: "blob.bin".into(),
+                mime_type: Some("application/octet-stream".into()),
+            },
+        ];
+        for item in &items {
+            assert_eq!(&round_trip(item), item);
+        }
+    }
+
+    #[test]
+    fn share_items_use_tagged_camel_case_json() {
+        let item = ShareItem::File {
+            url: "/tmp/report.pdf".into(),
+            mime_type: Some("application/pdf".into()),
+        };
+        assert_eq!(
+            serde_json::to_value(&item).expect("serialize"),
+            serde_json::json!({
+                "type": "file",
+                "url": "/tmp/report.pdf",
+                "mimeType": "application/pdf",
+            })
+        );
+    }
+
+    #[test]
+    fn share_items_deserialize_from_js_shape() {
+        let items: Vec<ShareItem> = serde_json::from_value(serde_json::json!([
+            { "type": "text", "text": "caption" },
+            { "type": "url", "url": "https://tauri.app" },
+            { "type": "file", "url": "file:///tmp/a.png" },
+            { "type": "bytes", "data": [104, 105], "name": "hi.txt", "mimeType": "text/plain" },
+        ]))
+        .expect("deserialize");
+        assert_eq!(
+            items,
+            vec![
+                ShareItem::Text {
+                    text: "caption".into()
+                },
+                ShareItem::Url {
+                    url: "https://tauri.app".into()
+                },
+                ShareItem::File {
+                    url: "file:///tmp/a.png".into(),
+                    mime_type: None,
+                },
+                ShareItem::Bytes {
+                    data: b"hi".to_vec(),
+                    name: "hi.txt".into(),
+                    mime_type: Some("text/plain".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_item_type_is_rejected() {
+        let result: Result<ShareItem, _> =
+            serde_json::from_value(serde_json::json!({ "type": "video", "url": "x" }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn capabilities_decide_which_items_can_be_shared() {
+        let text = ShareItem::Text {
+            text: "Hello".into(),
+        };
+        let file = ShareItem::File {
+            url: "/tmp/a.pdf".into(),
+            mime_type: None,
+        };
+        let bytes = ShareItem::Bytes {
+            data: vec![1],
+            name: "b.bin".into(),
+            mime_type: None,
+        };
+        let single_file = ShareCapabilities {
+            text: true,
+            files: true,
+            ..ShareCapabilities::default()
+        };
+
+        assert!(single_file.supports(&[text.clone(), file.clone()]));
+        assert!(!single_file.supports(&[file.clone(), bytes.clone()]));
+        assert!(!single_file.supports(&[ShareItem::Url {
+            url: "https://example.com".into()
+        }]));
+        assert!(!single_file.supports(&[]));
+        assert!(ShareCapabilities {
+            multiple_files: true,
+            ..single_file
+        }
+        .supports(&[file, bytes]));
+        assert!(!ShareCapabilities::default().supports(&[text]));
+    }
+
+    #[test]
+    fn share_payload_flattens_options() {
+        let payload = SharePayload {
+            items: vec![ShareItem::Text { text: "hi".into() }],
+            options: ShareOptions {
+                title: Some("Title".into()),
+                position: None,
+                strip_metadata: None,
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&payload).expect("serialize"),
+            serde_json::json!({
+                "items": [{ "type": "text", "text": "hi" }],
+                "title": "Title",
+            })
+        );
+    }
+}
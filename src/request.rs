@@ -0,0 +1,372 @@
+use std::path::Path;
+
+use tauri::{Manager, Runtime, Url, Webview, WebviewWindow, Window};
+
+use crate::models::{
+    ArchiveOptions, ShareFileOptions, ShareFilesOptions, ShareItem, ShareOptions, SharePosition,
+    ShareTextOptions, SharedFile,
+};
+use crate::{Error, ShareKit};
+
+/// Windows and webviews a share sheet can be attached to.
+pub trait ShareParent<R: Runtime> {
+    /// The window the share UI is presented over.
+    fn share_window(&self) -> crate::Result<WebviewWindow<R>>;
+}
+
+impl<R: Runtime> ShareParent<R> for WebviewWindow<R> {
+    fn share_window(&self) -> crate::Result<Self> {
+        Ok(self.clone())
+    }
+}
+
+impl<R: Runtime> ShareParent<R> for Webview<R> {
+    fn share_window(&self) -> crate::Result<WebviewWindow<R>> {
+        self.get_webview_window(self.window().label())
+            .ok_or(Error::WindowNotFound)
+    }
+}
+
+impl<R: Runtime> ShareParent<R> for Window<R> {
+    fn share_window(&self) -> crate::Result<WebviewWindow<R>> {
+        self.get_webview_window(self.label())
+            .ok_or(Error::WindowNotFound)
+    }
+}
+
+/// Builder for a share started from Rust, created with
+/// [`ShareExt::share_request`].
+///
+/// ```no_run
+/// # async fn run(window: tauri::WebviewWindow) -> tauri_plugin_sharekit::Result<()> {
+/// use tauri_plugin_sharekit::ShareExt;
+///
+/// window
+///     .share_request()
+///     .file("/tmp/report.pdf")
+///     .mime("application/pdf")
+///     .title("Report")
+///     .send()
+///     .await
+/// # }
+/// ```
+#[must_use = "a share request does nothing until `send` is awaited"]
+pub struct ShareRequest<'a, R: Runtime> {
+    share: &'a ShareKit<R>,
+    window: crate::Result<WebviewWindow<R>>,
+    content: ShareContent,
+}
+
+impl<'a, R: Runtime> ShareRequest<'a, R> {
+    pub(crate) fn new(share: &'a ShareKit<R>, window: crate::Result<WebviewWindow<R>>) -> Self {
+        Self {
+            share,
+            window,
+            content: ShareContent::default(),
+        }
+    }
+
+    /// Adds plain text.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.content.push(ShareItem::Text { text: text.into() });
+        self
+    }
+
+    /// Adds a link. `file://` URLs are shared as files.
+    pub fn url(mut self, url: Url) -> Self {
+        self.content.push(url_item(url));
+        self
+    }
+
+    /// Adds a file.
+    pub fn file(mut self, path: impl AsRef<Path>) -> Self {
+        self.content.push(file_item(path.as_ref()));
+        self
+    }
+
+    /// Sets the MIME type of the file or text added last.
+    pub fn mime(mut self, mime_type: impl Into<String>) -> Self {
+        self.content.set_mime(mime_type.into());
+        self
+    }
+
+    /// Sets the title of the share sheet.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.content.title = Some(title.into());
+        self
+    }
+
+    /// Sets the email subject of a lone text.
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.content.subject = Some(subject.into());
+        self
+    }
+
+    /// Sets where the share sheet is anchored (iPad/macOS only).
+    pub const fn position(mut self, position: SharePosition) -> Self {
+        self.content.position = Some(position);
+        self
+    }
+
+    /// Packs the files into an archive and shares that instead.
+    pub fn archive(mut self, options: ArchiveOptions) -> Self {
+        self.content.archive = Some(options);
+        self
+    }
+
+    /// Shares copies of the files without privacy metadata, or the files as
+    /// they are with `false`, whatever the configured `stripMetadata`.
+    pub const fn strip_metadata(mut self, strip: bool) -> Self {
+        self.content.strip_metadata = Some(strip);
+        self
+    }
+
+    /// Presents the share UI with the collected content.
+    pub async fn send(self) -> crate::Result<()> {
+        let window = self.window?;
+        match self.content.into_call()? {
+            ShareCall::Text(text, options) => self.share.share_text(window, text, options).await,
+            ShareCall::File(url, options) => self.share.share_file(window, url, options).await,
+            ShareCall::Files(files, options) => {
+                self.share.share_files(window, files, options).await
+            }
+            ShareCall::Items(items, options) => self.share.share(window, items, options).await,
+        }
+    }
+}
+
+fn url_item(url: Url) -> ShareItem {
+    let path = (url.scheme() == "file")
+        .then(|| url.to_file_path().ok())
+        .flatten();
+    path.map_or_else(
+        || ShareItem::Url { url: url.into() },
+        |path| file_item(&path),
+    )
+}
+
+fn file_item(path: &Path) -> ShareItem {
+    ShareItem::File {
+        url: path.to_string_lossy().into_owned(),
+        mime_type: None,
+    }
+}
+
+/// Content collected by a [`ShareRequest`].
+#[derive(Debug, Default)]
+struct ShareContent {
+    items: Vec<ShareItem>,
+    text_mime_type: Option<String>,
+    title: Option<String>,
+    subject: Option<String>,
+    position: Option<SharePosition>,
+    archive: Option<ArchiveOptions>,
+    strip_metadata: Option<bool>,
+}
+
+/// The `ShareKit` method a [`ShareRequest`] ends up calling.
+#[derive(Debug)]
+enum ShareCall {
+    Text(String, ShareTextOptions),
+    File(String, ShareFileOptions),
+    Files(Vec<SharedFile>, ShareFilesOptions),
+    Items(Vec<ShareItem>, ShareOptions),
+}
+
+impl ShareContent {
+    fn push(&mut self, item: ShareItem) {
+        self.items.push(item);
+    }
+
+    fn set_mime(&mut self, mime: String) {
+        match self.items.last_mut() {
+            Some(ShareItem::File { mime_type, .. }) => *mime_type = Some(mime),
+            Some(ShareItem::Text { .. }) => self.text_mime_type = Some(mime),
+            _ => {}
+        }
+    }
+
+    /// Picks the most specific `ShareKit` method for the content, so a lone
+    /// text or file keeps its dedicated platform behaviour.
+    fn into_call(mut self) -> crate::Result<ShareCall> {
+        if let [ShareItem::Text { text }] = self.items.as_mut_slice() {
+            return Ok(ShareCall::Text(
+                std::mem::take(text),
+                ShareTextOptions {
+                    mime_type: self.text_mime_type,
+                    title: self.title,
+                    subject: self.subject,
+                    position: self.position,
+                },
+            ));
+        }
+
+        if self.items.is_empty() {
+            return Err(Error::InvalidInput("nothing to share".into()));
+        }
+
+        if !self
+            .items
+            .iter()
+            .all(|item| matches!(item, ShareItem::File { .. }))
+        {
+            return Ok(ShareCall::Items(
+                self.items,
+                ShareOptions {
+                    title: self.title,
+                    position: self.position,
+                    strip_metadata: self.strip_metadata,
+                },
+            ));
+        }
+
+        let mut files: Vec<SharedFile> = self
+            .items
+            .into_iter()
+            .filter_map(|item| match item {
+                ShareItem::File { url, mime_type } => Some(SharedFile { url, mime_type }),
+                _ => None,
+            })
+            .collect();
+        if files.len() == 1 {
+            let SharedFile { url, mime_type } = files.remove(0);
+            return Ok(ShareCall::File(
+                url,
+                ShareFileOptions {
+                    mime_type,
+                    title: self.title,
+                    file_name: None,
+                    position: self.position,
+                    archive: self.archive,
+                    image: None,
+                    strip_metadata: self.strip_metadata,
+                    encryption: None,
+                },
+            ));
+        }
+        Ok(ShareCall::Files(
+            files,
+            ShareFilesOptions {
+                title: self.title,
+                position: self.position,
+                archive: self.archive,
+                strip_metadata: self.strip_metadata,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(items: Vec<ShareItem>) -> ShareContent {
+        ShareContent {
+            items,
+            ..ShareContent::default()
+        }
+    }
+
+    fn file(url: &str, mime_type: Option<&str>) -> ShareItem {
+        ShareItem::File {
+            url: url.into(),
+            mime_type: mime_type.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn lone_text_uses_share_text() {
+        let mut content = content(vec![ShareItem::Text {
+            text: "Hello".into(),
+        }]);
+        content.set_mime("text/markdown".into());
+        content.title = Some("Greeting".into());
+        content.subject = Some("Hi".into());
+
+        let ShareCall::Text(text, options) = content.into_call().expect("call") else {
+            panic!("expected share_text");
+        };
+        assert_eq!(text, "Hello");
+        assert_eq!(options.mime_type.as_deref(), Some("text/markdown"));
+        assert_eq!(options.title.as_deref(), Some("Greeting"));
+        assert_eq!(options.subject.as_deref(), Some("Hi"));
+    }
+
+    #[test]
+    fn lone_file_uses_share_file() {
+        let mut content = content(vec![file("/tmp/report.pdf", None)]);
+        content.set_mime("application/pdf".into());
+        content.title = Some("Report".into());
+
+        let ShareCall::File(url, options) = content.into_call().expect("call") else {
+            panic!("expected share_file");
+        };
+        assert_eq!(url, "/tmp/report.pdf");
+        assert_eq!(options.mime_type.as_deref(), Some("application/pdf"));
+        assert_eq!(options.title.as_deref(), Some("Report"));
+    }
+
+    #[test]
+    fn several_files_use_share_files() {
+        let content = content(vec![
+            file("/tmp/a.jpg", Some("image/jpeg")),
+            file("/tmp/b.png", None),
+        ]);
+
+        let ShareCall::Files(files, _) = content.into_call().expect("call") else {
+            panic!("expected share_files");
+        };
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].mime_type.as_deref(), Some("image/jpeg"));
+        assert_eq!(files[1].url, "/tmp/b.png");
+    }
+
+    #[test]
+    fn mixed_content_uses_share() {
+        let items = vec![
+            ShareItem::Text {
+                text: "Report".into(),
+            },
+            file("/tmp/report.pdf", None),
+        ];
+
+        let ShareCall::Items(shared, _) = content(items.clone()).into_call().expect("call") else {
+            panic!("expected share");
+        };
+        assert_eq!(shared, items);
+    }
+
+    #[test]
+    fn mime_applies_to_the_last_file() {
+        let mut content = content(vec![file("/tmp/a", None), file("/tmp/b", None)]);
+        content.set_mime("image/png".into());
+
+        assert_eq!(
+            content.items,
+            vec![file("/tmp/a", None), file("/tmp/b", Some("image/png"))]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_urls_become_file_items() {
+        let url = Url::parse("file:///tmp/a%20b.pdf").expect("url");
+        assert_eq!(url_item(url), file("/tmp/a b.pdf", None));
+
+        let url = Url::parse("https://tauri.app/").expect("url");
+        assert_eq!(
+            url_item(url),
+            ShareItem::Url {
+                url: "https://tauri.app/".into()
+            }
+        );
+    }
+
+    #[test]
+    fn empty_request_is_rejected() {
+        assert!(matches!(
+            ShareContent::default().into_call(),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,253 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::{
+    ipc::{CommandScope, GlobalScope, ScopeObject},
+    scope::fs::Scope,
+    utils::{acl::Value, config::FsScope},
+    AppHandle, Manager, Runtime, Url,
+};
+
+use crate::{Error, ShareExt};
+
+/// Path pattern of a `sharekit` scope entry, e.g. `$DOCUMENT/**`.
+///
+/// Patterns may start with the same base directory variables as the fs
+/// plugin scopes.
+#[derive(Debug)]
+pub struct Entry {
+    /// `None` when the pattern uses a base directory that does not exist on
+    /// this platform.
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EntryRaw {
+    Value(PathBuf),
+    Object { path: PathBuf },
+}
+
+impl ScopeObject for Entry {
+    type Error = tauri::Error;
+
+    fn deserialize<R: Runtime>(app: &AppHandle<R>, raw: Value) -> Result<Self, Self::Error> {
+        let path = match serde_json::from_value(raw.into())? {
+            EntryRaw::Value(path) | EntryRaw::Object { path } => path,
+        };
+
+        match app.path().parse(path) {
+            Ok(path) => Ok(Self { path: Some(path) }),
+            #[cfg(not(target_os = "android"))]
+            Err(tauri::Error::UnknownPath) => Ok(Self { path: None }),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Checks every file in `urls` against the command and global scopes and
+/// the `scope` section of the plugin configuration.
+///
+/// Fails with [`Error::PathNotAllowed`] for the first file outside the scope,
+/// so nothing is handed to the platform backend.
+pub fn ensure_allowed<'a, R: Runtime>(
+    app: &AppHandle<R>,
+    command_scope: &CommandScope<Entry>,
+    global_scope: &GlobalScope<Entry>,
+    urls: impl IntoIterator<Item = &'a str>,
+) -> crate::Result<()> {
+    let paths: Vec<_> = urls.into_iter().map(file_path).collect();
+    ensure_paths_allowed(app, command_scope, global_scope, &paths).map(drop)
+}
+
+/// Like [`ensure_allowed`], but replaces every URL in `urls` with the
+/// canonical path that was checked, so the file shared is the one that was
+/// allowed even if a symlink on the way to it changes afterwards.
+pub fn resolve_allowed<'a, R: Runtime>(
+    app: &AppHandle<R>,
+    command_scope: &CommandScope<Entry>,
+    global_scope: &GlobalScope<Entry>,
+    urls: impl IntoIterator<Item = &'a mut String>,
+) -> crate::Result<()> {
+    let mut urls: Vec<_> = urls.into_iter().collect();
+    let paths: Vec<_> = urls.iter().map(|url| file_path(url)).collect();
+    let canonical = ensure_paths_allowed(app, command_scope, global_scope, &paths)?;
+    for (url, path) in urls.iter_mut().zip(canonical) {
+        **url = path.to_string_lossy().into_owned();
+    }
+    Ok(())
+}
+
+/// Like [`ensure_allowed`], for paths such as the contents of a directory
+/// that is shared as an archive. Returns the canonical paths that were
+/// checked, in the same order.
+pub fn ensure_paths_allowed<R: Runtime>(
+    app: &AppHandle<R>,
+    command_scope: &CommandScope<Entry>,
+    global_scope: &GlobalScope<Entry>,
+    paths: &[PathBuf],
+) -> crate::Result<Vec<PathBuf>> {
+    let config = &app.share().config().scope;
+    let allow = command_scope
+        .allows()
+        .iter()
+        .chain(global_scope.allows())
+        .filter_map(|entry| entry.path.clone())
+        .chain(config.allow.iter().cloned())
+        .collect();
+    let deny = command_scope
+        .denies()
+        .iter()
+        .chain(global_scope.denies())
+        .filter_map(|entry| entry.path.clone())
+        .chain(config.deny.iter().cloned())
+        .collect();
+    let scope = build_scope(app, allow, deny)?;
+
+    paths.iter().map(|path| check(&scope, path)).collect()
+}
+
+fn build_scope<R: Runtime, M: Manager<R>>(
+    manager: &M,
+    allow: Vec<PathBuf>,
+    deny: Vec<PathBuf>,
+) -> crate::Result<Scope> {
+    let config = FsScope::Scope {
+        allow,
+        deny,
+        require_literal_leading_dot: None,
+    };
+    Scope::new(manager, &config).map_err(|e| std::io::Error::other(e).into())
+}
+
+/// Resolves symlinks and relative components of `path` before matching it,
+/// so a link inside an allowed directory cannot point somewhere else.
+/// Returns the resolved path.
+fn check(scope: &Scope, path: &Path) -> crate::Result<PathBuf> {
+    let canonical = std::fs::canonicalize(path)?;
+    if scope.is_allowed(&canonical) {
+        Ok(canonical)
+    } else {
+        Err(Error::PathNotAllowed(path.to_owned()))
+    }
+}
+
+/// Accepts both plain paths and `file://` URLs. [`ShareKit`](crate::ShareKit)
+/// hands backends plain paths only.
+pub fn file_path(url: &str) -> PathBuf {
+    Url::parse(url)
+        .ok()
+        .filter(|parsed| parsed.scheme() == "file")
+        .and_then(|parsed| parsed.to_file_path().ok())
+        .unwrap_or_else(|| PathBuf::from(url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn scope(allow: &[PathBuf], deny: &[PathBuf]) -> Scope {
+        let app = tauri::test::mock_app();
+        build_scope(app.handle(), allow.to_vec(), deny.to_vec()).expect("build scope")
+    }
+
+    #[test]
+    fn files_inside_allowed_directory_pass() {
+        let dir = TempDir::new("scope");
+        let file = dir.file("shared/report.pdf");
+        let scope = scope(&[dir.0.join("shared/**")], &[]);
+
+        check(&scope, &file).expect("allowed");
+        let url = Url::from_file_path(&file).expect("file url");
+        check(&scope, &file_path(url.as_str())).expect("allowed as file url");
+    }
+
+    #[test]
+    fn accepts_paths_and_file_urls() {
+        assert_eq!(file_path("/tmp/a b.pdf"), PathBuf::from("/tmp/a b.pdf"));
+        assert_eq!(
+            file_path("file:///tmp/a%20b.pdf"),
+            PathBuf::from("/tmp/a b.pdf")
+        );
+    }
+
+    #[test]
+    fn files_outside_scope_are_rejected() {
+        let dir = TempDir::new("scope");
+        let secret = dir.file("private/id_rsa");
+        let scope = scope(&[dir.0.join("shared/**")], &[]);
+
+        assert!(matches!(
+            check(&scope, &secret),
+            Err(Error::PathNotAllowed(path)) if path == secret
+        ));
+    }
+
+    #[test]
+    fn empty_scope_rejects_everything() {
+        let dir = TempDir::new("scope");
+        let file = dir.file("report.pdf");
+
+        assert!(matches!(
+            check(&scope(&[], &[]), &file),
+            Err(Error::PathNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn deny_takes_precedence_over_allow() {
+        let dir = TempDir::new("scope");
+        let file = dir.file("shared/secret.txt");
+        let scope = scope(&[dir.0.join("**")], &[dir.0.join("shared/secret.txt")]);
+
+        assert!(matches!(
+            check(&scope, &file),
+            Err(Error::PathNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn parent_components_are_resolved() {
+        let dir = TempDir::new("scope");
+        let report = dir.file("shared/report.pdf");
+        dir.file("private/secret.txt");
+        let scope = scope(&[dir.0.join("shared/**")], &[]);
+
+        assert_eq!(
+            check(&scope, &dir.0.join("shared/../shared/report.pdf")).expect("allowed"),
+            std::fs::canonicalize(report).expect("canonicalize")
+        );
+        assert!(matches!(
+            check(&scope, &dir.0.join("shared/../private/secret.txt")),
+            Err(Error::PathNotAllowed(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_resolved_before_matching() {
+        let dir = TempDir::new("scope");
+        let secret = dir.file("private/secret.txt");
+        dir.file("shared/report.pdf");
+        let link = dir.0.join("shared/link.txt");
+        std::os::unix::fs::symlink(&secret, &link).expect("symlink");
+        let scope = scope(&[dir.0.join("shared/**")], &[]);
+
+        assert!(matches!(
+            check(&scope, &link),
+            Err(Error::PathNotAllowed(path)) if path == link
+        ));
+    }
+
+    #[test]
+    fn missing_files_are_io_errors() {
+        let dir = TempDir::new("scope");
+        let scope = scope(&[dir.0.join("**")], &[]);
+
+        assert!(matches!(
+            check(&scope, &dir.0.join("missing.pdf")),
+            Err(Error::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,679 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Manager, Runtime};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::archive;
+use crate::config::Config;
+use crate::encryption::Encryption;
+use crate::models::{ArchiveFormat, ArchiveOptions, ImageOptions, ShareItem};
+use crate::{imaging, metadata};
+
+/// How long staged copies are kept when `stagingTtl` is not configured.
+#[allow(clippy::duration_suboptimal_units)] // `Duration::from_hours` needs Rust 1.91
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Directory that holds temporary copies of shared content: a `sharekit`
+/// directory inside the configured `stagingDir`, or `$APPCACHE/sharekit`.
+/// The plugin owns this directory, so the configured one may be shared with
+/// other files.
+pub fn staging_dir<R: Runtime>(app: &AppHandle<R>, config: &Config) -> crate::Result<PathBuf> {
+    let parent = match &config.staging_dir {
+        Some(dir) => dir.clone(),
+        None => app.path().app_cache_dir().map_err(io::Error::other)?,
+    };
+    Ok(parent.join("sharekit"))
+}
+
+/// Temporary copies of shared content.
+///
+/// Every share gets its own directory below [`root`](Self::root), readable
+/// only by the current user, so concurrent shares of files with the same
+/// name do not overwrite each other. Directories are deleted once the
+/// configured `stagingTtl` has passed, and leftovers from earlier runs are
+/// swept when the plugin starts.
+#[derive(Debug, Clone)]
+pub struct Staging {
+    root: PathBuf,
+    ttl: Duration,
+}
+
+impl Staging {
+    pub(crate) fn new<R: Runtime>(app: &AppHandle<R>, config: &Config) -> crate::Result<Self> {
+        Ok(Self {
+            root: staging_dir(app, config)?,
+            ttl: config.staging_ttl.map_or(DEFAULT_TTL, Duration::from_secs),
+        })
+    }
+
+    /// Directory holding the staged copies.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// How long staged copies are kept.
+    #[must_use]
+    pub const fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Writes `data` to a new share directory and returns the path of the
+    /// written file, named `name` as cleaned up by [`sanitize_file_name`].
+    pub fn stage_bytes(&self, name: &str, data: &[u8]) -> io::Result<PathBuf> {
+        self.share_dir()?.write(name, data)
+    }
+
+    /// Copies `source` into a new share directory under the name `name`, as
+    /// cleaned up by [`sanitize_file_name`], and returns the path of the copy.
+    /// `source` itself is left untouched.
+    pub fn stage_copy(&self, source: &Path, name: &str) -> io::Result<PathBuf> {
+        self.share_dir()?.copy(source, name)
+    }
+
+    /// Stages `source` the way it should be shared: named `name`,
+    /// re-encoded as `image` asks, without privacy metadata with `strip`,
+    /// and encrypted with `encryption`. Returns `None` when none of this
+    /// applies, so `source` can be shared as it is. `source` itself is left
+    /// untouched.
+    pub fn stage_file(
+        &self,
+        source: &Path,
+        name: Option<&str>,
+        image: Option<&ImageOptions>,
+        strip: bool,
+        encryption: Option<&Encryption>,
+    ) -> io::Result<Option<PathBuf>> {
+        let renamed = name.is_some();
+        let mut name = name.map_or_else(
+            || {
+                source
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            },
+            ToOwned::to_owned,
+        );
+        let processed = match image {
+            Some(options) => imaging::process_file(source, options)?,
+            None => None,
+        };
+        // Re-encoded images carry no metadata.
+        let content = match processed {
+            Some(image) => {
+                name = image.file_name(&name);
+                Some(image.data)
+            }
+            None if strip => metadata::strip_file(source)?,
+            None => None,
+        };
+        let path = match (content, encryption) {
+            (Some(data), Some(encryption)) => {
+                let len = Some(data.len() as u64);
+                self.stage_encrypted(data.as_slice(), len, &name, encryption)?
+            }
+            (Some(data), None) => self.stage_bytes(&name, &data)?,
+            (None, Some(encryption)) => {
+                let file = File::open(source)?;
+                let len = Some(file.metadata()?.len());
+                self.stage_encrypted(file, len, &name, encryption)?
+            }
+            (None, None) if renamed => self.stage_copy(source, &name)?,
+            (None, None) => return Ok(None),
+        };
+        Ok(Some(path))
+    }
+
+    /// Packs `sources` into a new archive in a new share directory and
+    /// returns its path. With `strip`, files are archived without their
+    /// privacy metadata. With an `encryption`, only the encrypted archive is
+    /// written. This reads every archived file, so call it off the async
+    /// runtime.
+    pub fn stage_archive(
+        &self,
+        sources: &[PathBuf],
+        options: &ArchiveOptions,
+        strip: bool,
+        encryption: Option<&Encryption>,
+    ) -> io::Result<PathBuf> {
+        let entries = archive::list(sources, options.symlinks)?;
+        let dir = self.share_dir()?;
+        let name = sanitize_file_name(&archive::file_name(sources, options));
+
+        // A zip archive can hold its files encrypted rather than being
+        // wrapped in another archive.
+        let password = encryption
+            .and_then(Encryption::zip_password)
+            .filter(|_| options.format == ArchiveFormat::Zip);
+        match encryption {
+            Some(encryption) if password.is_none() => {
+                let path = dir.unique_path(&encryption.file_name(&name));
+                encryption.encrypt(create_private_file(&path)?, &name, None, |out| {
+                    archive::write_stream(out, &entries, options, None, strip)
+                })?;
+                Ok(path)
+            }
+            _ => {
+                let path = dir.unique_path(&name);
+                let file = create_private_file(&path)?;
+                archive::write(file, &entries, options, password, strip)?;
+                Ok(path)
+            }
+        }
+    }
+
+    /// Encrypts `source`, the content of a file named `name`, into a new
+    /// share directory and returns the path of the encrypted file. The
+    /// plaintext is never written to the staging directory.
+    pub fn stage_encrypted(
+        &self,
+        mut source: impl io::Read,
+        len: Option<u64>,
+        name: &str,
+        encryption: &Encryption,
+    ) -> io::Result<PathBuf> {
+        let name = sanitize_file_name(name);
+        let path = self.share_dir()?.unique_path(&encryption.file_name(&name));
+        encryption.encrypt(create_private_file(&path)?, &name, len, |out| {
+            io::copy(&mut source, out).map(drop)
+        })?;
+        Ok(path)
+    }
+
+    /// Replaces every `Bytes` item with a `File` item pointing at a staged
+    /// copy. All copies of one call share a directory.
+    pub(crate) fn stage_items(&self, items: Vec<ShareItem>) -> io::Result<Vec<ShareItem>> {
+        if !items
+            .iter()
+            .any(|item| matches!(item, ShareItem::Bytes { .. }))
+        {
+            return Ok(items);
+        }
+
+        let dir = self.share_dir()?;
+        items
+            .into_iter()
+            .map(|item| match item {
+                ShareItem::Bytes {
+                    data,
+                    name,
+                    mime_type,
+                } => Ok(ShareItem::File {
+                    url: dir.write(&name, &data)?.to_string_lossy().into_owned(),
+                    mime_type,
+                }),
+                other => Ok(other),
+            })
+            .collect()
+    }
+
+    /// Copies every file in `sources` into one new share directory and
+    /// returns the paths of the copies, in the same order.
+    #[cfg_attr(not(mobile), allow(dead_code))]
+    pub(crate) fn stage_copies<'a>(
+        &self,
+        sources: impl IntoIterator<Item = &'a Path>,
+    ) -> io::Result<Vec<PathBuf>> {
+        let dir = self.share_dir()?;
+        sources
+            .into_iter()
+            .map(|source| {
+                let name = source.file_name().unwrap_or_default().to_string_lossy();
+                dir.copy(source, &name)
+            })
+            .collect()
+    }
+
+    /// Writes copies without privacy metadata of the files in `sources`
+    /// that have any into one new share directory. Returns the path of each
+    /// copy, in the same order, or `None` for files left as they are.
+    pub(crate) fn stage_stripped<'a>(
+        &self,
+        sources: impl IntoIterator<Item = &'a Path>,
+    ) -> io::Result<Vec<Option<PathBuf>>> {
+        let mut dir = None;
+        sources
+            .into_iter()
+            .map(|source| {
+                let Some(data) = metadata::strip_file(source)? else {
+                    return Ok(None);
+                };
+                let dir = match &mut dir {
+                    Some(dir) => dir,
+                    None => dir.insert(self.share_dir()?),
+                };
+                let name = source.file_name().unwrap_or_default().to_string_lossy();
+                dir.write(&name, &data).map(Some)
+            })
+            .collect()
+    }
+
+    /// Whether `path` is a staged copy, which does not need to be staged again.
+    #[cfg_attr(not(mobile), allow(dead_code))]
+    pub(crate) fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.root)
+    }
+
+    /// Deletes share directories older than the TTL, e.g. those left behind
+    /// when the app exited before their TTL passed.
+    pub fn sweep(&self) -> io::Result<()> {
+        self.remove_entries(|entry| {
+            let age = entry.metadata()?.modified()?.elapsed().unwrap_or_default();
+            Ok(age >= self.ttl)
+        })
+    }
+
+    /// Deletes every share directory, including copies that may still be
+    /// read by the app the user shared them with.
+    pub fn clear(&self) -> io::Result<()> {
+        self.remove_entries(|_| Ok(true))
+    }
+
+    /// Deletes the share directories that `filter` accepts. Anything else in
+    /// the staging directory was not created by [`share_dir`](Self::share_dir)
+    /// and is left alone.
+    fn remove_entries(
+        &self,
+        mut filter: impl FnMut(&fs::DirEntry) -> io::Result<bool>,
+    ) -> io::Result<()> {
+        let entries = match fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir()
+                || !is_share_dir_name(&entry.file_name().to_string_lossy())
+                || !filter(&entry)?
+            {
+                continue;
+            }
+            match fs::remove_dir_all(entry.path()) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the directory for one share and schedules its deletion.
+    fn share_dir(&self) -> io::Result<ShareDir> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        let path = self.root.join(format!(
+            "{nanos:x}-{}",
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        create_private_dir(&self.root, true)?;
+        create_private_dir(&path, false)?;
+
+        let ttl = self.ttl;
+        let expired = path.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(ttl).await;
+            let _ = tokio::fs::remove_dir_all(expired).await;
+        });
+        Ok(ShareDir(path))
+    }
+}
+
+/// Whether `name` has the `{nanos:x}-{counter}` form of the directories
+/// [`Staging::share_dir`] creates.
+fn is_share_dir_name(name: &str) -> bool {
+    name.split_once('-').is_some_and(|(nanos, counter)| {
+        !nanos.is_empty()
+            && !counter.is_empty()
+            && nanos.bytes().all(|b| b.is_ascii_hexdigit())
+            && counter.bytes().all(|b| b.is_ascii_digit())
+    })
+}
+
+/// Directory holding the files of a single share.
+struct ShareDir(PathBuf);
+
+impl ShareDir {
+    fn write(&self, name: &str, data: &[u8]) -> io::Result<PathBuf> {
+        let path = self.unique_path(name);
+        io::Write::write_all(&mut create_private_file(&path)?, data)?;
+        Ok(path)
+    }
+
+    fn copy(&self, source: &Path, name: &str) -> io::Result<PathBuf> {
+        let mut source = File::open(source)?;
+        let path = self.unique_path(name);
+        io::copy(&mut source, &mut create_private_file(&path)?)?;
+        Ok(path)
+    }
+
+    /// Path for `name`, cleaned up by [`sanitize_file_name`], inside the
+    /// directory. Numbered like `report (2).pdf` if a file of that name was
+    /// already staged for this share.
+    fn unique_path(&self, name: &str) -> PathBuf {
+        let name = sanitize_file_name(name);
+        let path = self.0.join(&name);
+        if !path.exists() {
+            return path;
+        }
+
+        let (stem, extension) = split_extension(&name);
+        let mut n = 2;
+        loop {
+            let path = self.0.join(format!("{stem} ({n}){extension}"));
+            if !path.exists() {
+                return path;
+            }
+            n += 1;
+        }
+    }
+}
+
+/// Longest file name, in bytes, that common file systems accept.
+const MAX_FILE_NAME_LEN: usize = 255;
+
+/// Turns `name` into a file name that is valid on every platform the
+/// receiving app may store it on.
+///
+/// Only the final path component is kept. The name is normalized to NFC,
+/// characters Windows rejects are replaced with `_`, trailing dots and
+/// spaces are removed, reserved device names such as `CON` get a leading
+/// `_`, and the stem is shortened so the name fits in 255 bytes.
+pub fn sanitize_file_name(name: &str) -> String {
+    let name: String = name.nfc().collect();
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let name = name.trim_start_matches(' ').trim_end_matches(['.', ' ']);
+    if name.is_empty() {
+        return "file".into();
+    }
+
+    let (stem, extension) = split_extension(name);
+    let device = stem.split('.').next().unwrap_or_default();
+    let prefix = if is_reserved_device_name(device) {
+        "_"
+    } else {
+        ""
+    };
+
+    let max_stem = MAX_FILE_NAME_LEN.saturating_sub(prefix.len() + extension.len());
+    if max_stem == 0 {
+        // An absurdly long extension; keep as much of the name as fits.
+        return truncate(name, MAX_FILE_NAME_LEN).to_owned();
+    }
+    format!("{prefix}{}{extension}", truncate(stem, max_stem))
+}
+
+/// Splits `name` into its stem and its extension including the dot. Names
+/// that only start with a dot, like `.env`, have no extension.
+pub fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => name.split_at(dot),
+        _ => (name, ""),
+    }
+}
+
+fn is_reserved_device_name(name: &str) -> bool {
+    let upper = name.trim_end_matches(' ').to_ascii_uppercase();
+    match upper.as_bytes() {
+        b"CON" | b"PRN" | b"AUX" | b"NUL" | b"CONIN$" | b"CONOUT$" => true,
+        [b'C', b'O', b'M', digit] | [b'L', b'P', b'T', digit] => digit.is_ascii_digit(),
+        _ => false,
+    }
+}
+
+/// The longest prefix of `s` of at most `max` bytes that ends on a
+/// character boundary.
+fn truncate(s: &str, max: usize) -> &str {
+    let mut end = max.min(s.len());
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+fn create_private_dir(path: &Path, recursive: bool) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(recursive);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(path)
+}
+
+fn create_private_file(path: &Path) -> io::Result<File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn staging(root: &TempDir, ttl: Duration) -> Staging {
+        Staging {
+            root: root.0.join("staging"),
+            ttl,
+        }
+    }
+
+    #[test]
+    fn each_share_gets_its_own_directory() {
+        let dir = TempDir::new("staging");
+        let staging = staging(&dir, DEFAULT_TTL);
+
+        let first = staging.stage_bytes("photo.jpg", b"first").expect("stage");
+        let second = staging.stage_bytes("photo.jpg", b"second").expect("stage");
+
+        assert_ne!(first.parent(), second.parent());
+        assert_eq!(first.file_name(), second.file_name());
+        assert_eq!(fs::read(&first).expect("read"), b"first");
+        assert_eq!(fs::read(&second).expect("read"), b"second");
+    }
+
+    #[test]
+    fn names_are_reduced_to_their_final_component() {
+        let dir = TempDir::new("staging");
+        let staging = staging(&dir, DEFAULT_TTL);
+
+        let path = staging
+            .stage_bytes("../../evil.sh", b"data")
+            .expect("stage");
+        assert!(path.starts_with(staging.root()));
+        assert_eq!(path.file_name(), Some("evil.sh".as_ref()));
+
+        let path = staging.stage_bytes("..", b"data").expect("stage");
+        assert_eq!(path.file_name(), Some("file".as_ref()));
+    }
+
+    #[test]
+    fn file_names_are_sanitized() {
+        let cases = [
+            ("Invoice-2026-10.pdf", "Invoice-2026-10.pdf"),
+            ("reports/q3.pdf", "q3.pdf"),
+            ("C:\\Users\\me\\q3.pdf", "q3.pdf"),
+            ("what?<now>|\"*:.txt", "what__now_____.txt"),
+            ("tab\there\n.txt", "tab_here_.txt"),
+            ("  trailing. . ", "trailing"),
+            ("...", "file"),
+            ("", "file"),
+            (".env", ".env"),
+            ("CON", "_CON"),
+            ("con.txt", "_con.txt"),
+            ("Lpt9.tar.gz", "_Lpt9.tar.gz"),
+            ("COM10.txt", "COM10.txt"),
+            ("console.txt", "console.txt"),
+            // "é" as "e" followed by a combining acute accent.
+            ("Cafe\u{301}.txt", "Caf\u{e9}.txt"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(sanitize_file_name(name), expected, "{name:?}");
+        }
+    }
+
+    #[test]
+    fn long_file_names_keep_their_extension() {
+        let name = format!("{}.pdf", "a".repeat(300));
+        let sanitized = sanitize_file_name(&name);
+        assert_eq!(sanitized.len(), MAX_FILE_NAME_LEN);
+        assert!(sanitized.ends_with("a.pdf"));
+
+        // Multi-byte characters are not split.
+        let name = format!("{}.txt", "\u{e9}".repeat(200));
+        let sanitized = sanitize_file_name(&name);
+        assert!(sanitized.len() <= MAX_FILE_NAME_LEN);
+        assert!(sanitized.ends_with("\u{e9}.txt"));
+    }
+
+    #[test]
+    fn copies_can_be_renamed_without_touching_the_source() {
+        let dir = TempDir::new("staging");
+        let source = dir.file("data/a91f3c.bin");
+
+        let copy = staging(&dir, DEFAULT_TTL)
+            .stage_copy(&source, "Invoice: 2026-10.pdf")
+            .expect("stage");
+
+        assert_eq!(copy.file_name(), Some("Invoice_ 2026-10.pdf".as_ref()));
+        assert_eq!(fs::read(&copy).expect("read copy"), b"data");
+        assert_eq!(fs::read(&source).expect("read source"), b"data");
+    }
+
+    #[test]
+    fn copies_with_the_same_name_are_numbered() {
+        let dir = TempDir::new("staging");
+        let a = dir.file("a/report.pdf");
+        let b = dir.file("b/report.pdf");
+        let c = dir.file("c/README");
+        let d = dir.file("d/README");
+
+        let copies = staging(&dir, DEFAULT_TTL)
+            .stage_copies([a.as_path(), b.as_path(), c.as_path(), d.as_path()])
+            .expect("stage");
+
+        let names: Vec<_> = copies
+            .iter()
+            .map(|path| path.file_name().expect("name").to_string_lossy())
+            .collect();
+        assert_eq!(
+            names,
+            ["report.pdf", "report (2).pdf", "README", "README (2)"]
+        );
+        assert!(copies
+            .iter()
+            .all(|path| path.parent() == copies[0].parent()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn staged_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("staging");
+        let path = staging(&dir, DEFAULT_TTL)
+            .stage_bytes("secret.txt", b"data")
+            .expect("stage");
+
+        let mode = |path: &Path| fs::metadata(path).expect("metadata").permissions().mode() & 0o777;
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(mode(path.parent().expect("share dir")), 0o700);
+    }
+
+    #[test]
+    fn sweep_removes_only_expired_shares() {
+        let dir = TempDir::new("staging");
+        let fresh = staging(&dir, DEFAULT_TTL);
+        let path = fresh.stage_bytes("a.txt", b"data").expect("stage");
+
+        fresh.sweep().expect("sweep");
+        assert!(path.exists());
+
+        staging(&dir, Duration::ZERO).sweep().expect("sweep");
+        assert!(!path.exists());
+        assert!(fresh.root().exists());
+    }
+
+    #[test]
+    fn clear_empties_the_staging_directory() {
+        let dir = TempDir::new("staging");
+        let staging = staging(&dir, DEFAULT_TTL);
+        let path = staging.stage_bytes("a.txt", b"data").expect("stage");
+
+        staging.clear().expect("clear");
+        assert!(!path.exists());
+        assert_eq!(fs::read_dir(staging.root()).expect("read dir").count(), 0);
+    }
+
+    #[test]
+    fn only_share_directories_are_removed() {
+        let dir = TempDir::new("staging");
+        let staging = staging(&dir, Duration::ZERO);
+        let path = staging.stage_bytes("a.txt", b"data").expect("stage");
+        let notes = staging.root().join("notes");
+        let log = staging.root().join("18c2f-3.log");
+        fs::create_dir(&notes).expect("create dir");
+        fs::write(&log, b"log").expect("write");
+
+        staging.sweep().expect("sweep");
+        staging.clear().expect("clear");
+        assert!(!path.exists());
+        assert!(notes.exists());
+        assert!(log.exists());
+    }
+
+    #[test]
+    fn share_directory_names_are_recognized() {
+        assert!(is_share_dir_name("18c2f0a9b3e4d5f6-0"));
+        assert!(is_share_dir_name("0-12"));
+        for name in ["", "-", "abc", "18c2f-", "-3", "xyz-3", "18c2f-3a", "a-b-c"] {
+            assert!(!is_share_dir_name(name), "{name:?}");
+        }
+    }
+
+    #[test]
+    fn missing_root_is_not_an_error() {
+        let dir = TempDir::new("staging");
+        let staging = staging(&dir, DEFAULT_TTL);
+        staging.sweep().expect("sweep");
+        staging.clear().expect("clear");
+    }
+
+    #[test]
+    fn stage_items_replaces_bytes_with_files() {
+        let dir = TempDir::new("staging");
+        let items = staging(&dir, DEFAULT_TTL)
+            .stage_items(vec![
+                ShareItem::Text { text: "hi".into() },
+                ShareItem::Bytes {
+                    data: b"data".to_vec(),
+                    name: "a.bin".into(),
+                    mime_type: Some("application/octet-stream".into()),
+                },
+            ])
+            .expect("stage");
+
+        let [ShareItem::Text { .. }, ShareItem::File { url, mime_type }] = items.as_slice() else {
+            panic!("unexpected items: {items:?}");
+        };
+        assert_eq!(fs::read(url).expect("read"), b"data");
+        assert_eq!(mime_type.as_deref(), Some("application/octet-stream"));
+    }
+}
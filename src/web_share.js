@@ -0,0 +1,169 @@
+// `navigator.share` and `navigator.canShare` for webviews without the Web
+// Share API, installed by `Builder::web_share_polyfill`. The plugin checks
+// the data again before sharing; the checks here only make `canShare` and
+// the synchronous `TypeError`s behave like in browsers.
+(function () {
+  "use strict";
+
+  if (
+    typeof navigator === "undefined" ||
+    typeof navigator.share === "function"
+  ) {
+    return;
+  }
+
+  const MAX_FILES = 10;
+  const MAX_TOTAL_SIZE = 50 * 1024 * 1024;
+  const LOCAL_SCHEMES = [
+    "about:",
+    "blob:",
+    "data:",
+    "file:",
+    "javascript:",
+    "ws:",
+    "wss:",
+  ];
+
+  // Files without a type are let through; the plugin infers theirs.
+  function isPermitted(type) {
+    const essence = type.split(";")[0].trim().toLowerCase();
+    return (
+      essence === "" ||
+      /^(image|audio|video|text)\//.test(essence) ||
+      essence === "application/pdf"
+    );
+  }
+
+  function resolveUrl(url) {
+    try {
+      const parsed = new URL(String(url), document.baseURI);
+      return LOCAL_SCHEMES.includes(parsed.protocol) ? null : parsed.href;
+    } catch {
+      return null;
+    }
+  }
+
+  // Why `data` cannot be shared, or `null` if it can.
+  function problem(data) {
+    if (data === null || typeof data !== "object") {
+      return "ShareData must be an object";
+    }
+    const files = data.files === undefined ? [] : Array.from(data.files);
+    if (
+      data.title === undefined &&
+      data.text === undefined &&
+      data.url === undefined &&
+      files.length === 0
+    ) {
+      return "ShareData needs a title, text, url or files";
+    }
+    if (data.url !== undefined && resolveUrl(data.url) === null) {
+      return "Invalid URL";
+    }
+    if (!files.every((file) => file instanceof File)) {
+      return "ShareData files must be File objects";
+    }
+    if (files.length > MAX_FILES) {
+      return `At most ${MAX_FILES} files can be shared`;
+    }
+    if (files.reduce((total, file) => total + file.size, 0) > MAX_TOTAL_SIZE) {
+      return "Files may add up to at most 50 MiB";
+    }
+    if (!files.every((file) => isPermitted(file.type))) {
+      return "Only images, audio, video, text and PDF documents can be shared";
+    }
+    return null;
+  }
+
+  // Rejections of the plugin as the DOM exceptions of the Web Share API.
+  function domError(error) {
+    const message = (error && error.message) || String(error);
+    switch (error && error.code) {
+      case "CANCELLED":
+        return new DOMException(message, "AbortError");
+      case "INVALID_INPUT":
+        return new TypeError(message);
+      case "UNSUPPORTED_PLATFORM":
+      case "PATH_NOT_ALLOWED":
+        return new DOMException(message, "NotAllowedError");
+      default:
+        return new DOMException(message, "DataError");
+    }
+  }
+
+  let pending = false;
+
+  async function share(data = {}) {
+    const reason = problem(data);
+    if (reason !== null) {
+      throw new TypeError(reason);
+    }
+    if (pending) {
+      throw new DOMException(
+        "A share is already in progress",
+        "InvalidStateError",
+      );
+    }
+    if (navigator.userActivation && !navigator.userActivation.isActive) {
+      throw new DOMException(
+        "Sharing requires a user gesture",
+        "NotAllowedError",
+      );
+    }
+
+    pending = true;
+    try {
+      const files = Array.from(data.files || []);
+      const contents = await Promise.all(
+        files.map((file) => file.arrayBuffer()),
+      );
+      const body = new Uint8Array(
+        contents.reduce((total, content) => total + content.byteLength, 0),
+      );
+      let offset = 0;
+      for (const content of contents) {
+        body.set(new Uint8Array(content), offset);
+        offset += content.byteLength;
+      }
+      const options = {
+        title: data.title === undefined ? undefined : String(data.title),
+        text: data.text === undefined ? undefined : String(data.text),
+        url: data.url === undefined ? undefined : resolveUrl(data.url),
+        files: files.map((file, i) => ({
+          name: file.name,
+          type: file.type,
+          size: contents[i].byteLength,
+        })),
+      };
+      await window.__TAURI_INTERNALS__.invoke(
+        "plugin:sharekit|share_web",
+        body,
+        {
+          headers: {
+            "x-sharekit-options": encodeURIComponent(JSON.stringify(options)),
+          },
+        },
+      );
+    } catch (error) {
+      throw error instanceof Error ? error : domError(error);
+    } finally {
+      pending = false;
+    }
+  }
+
+  function canShare(data) {
+    return problem(data) === null;
+  }
+
+  for (const [name, value] of [
+    ["share", share],
+    ["canShare", canShare],
+  ]) {
+    Object.defineProperty(Navigator.prototype, name, {
+      value,
+      configurable: true,
+      enumerable: true,
+      writable: true,
+    });
+  }
+})();
@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::data::BodyOptions;
+use crate::models::ShareItem;
+use crate::{link, mime, Error};
+use serde::Deserialize;
+
+/// Most files one share may carry, as in Chromium.
+const MAX_FILES: usize = 10;
+
+/// Most bytes the files of one share may add up to, as in Chromium.
+const MAX_TOTAL_SIZE: u64 = 50 * 1024 * 1024;
+
+/// `ShareData` passed to the `navigator.share` polyfill. The content of
+/// the files is sent as the request body, one after the other.
+#[derive(Debug, Default, Deserialize)]
+pub struct WebShareData {
+    pub title: Option<String>,
+    pub text: Option<String>,
+    /// Resolved against the document's base URL by the polyfill.
+    pub url: Option<String>,
+    #[serde(default)]
+    pub files: Vec<WebShareFile>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebShareFile {
+    pub name: String,
+    /// `File.type`, empty if the browser does not know it.
+    #[serde(rename = "type", default)]
+    pub mime_type: String,
+    pub size: u64,
+}
+
+impl BodyOptions for WebShareData {}
+
+impl WebShareData {
+    /// Validates the data against the Web Share Level 2 rules and splits
+    /// `body` into the shared files. Returns the items to share and the
+    /// title of the share.
+    ///
+    /// Files must be images, audio, video, text or PDF documents, like in
+    /// browsers; files without a type get one from `mime_types`, their
+    /// content or their extension.
+    pub fn into_items(
+        self,
+        body: &[u8],
+        mime_types: &BTreeMap<String, String>,
+    ) -> crate::Result<(Vec<ShareItem>, Option<String>)> {
+        if self.title.is_none()
+            && self.text.is_none()
+            && self.url.is_none()
+            && self.files.is_empty()
+        {
+            return Err(invalid("ShareData needs a title, text, url or files"));
+        }
+        if self.files.len() > MAX_FILES {
+            return Err(invalid(format!("at most {MAX_FILES} files can be shared")));
+        }
+        let total = self
+            .files
+            .iter()
+            .try_fold(0_u64, |total, file| total.checked_add(file.size))
+            .unwrap_or(u64::MAX);
+        if total > MAX_TOTAL_SIZE {
+            return Err(invalid(format!(
+                "files may add up to at most {} MiB",
+                MAX_TOTAL_SIZE / 1024 / 1024
+            )));
+        }
+        if total != body.len() as u64 {
+            return Err(invalid(format!(
+                "expected {total} bytes of file content, got {}",
+                body.len()
+            )));
+        }
+
+        let mut items = Vec::new();
+        if let Some(text) = self.text {
+            items.push(ShareItem::Text { text });
+        }
+        if let Some(url) = self.url {
+            items.push(ShareItem::Url {
+                url: link::normalize(&url)?,
+            });
+        }
+        let mut rest = body;
+        for file in self.files {
+            let (data, tail) = usize::try_from(file.size)
+                .ok()
+                .and_then(|size| rest.split_at_checked(size))
+                .ok_or_else(|| invalid("file sizes do not match the file content"))?;
+            rest = tail;
+            if file.name.is_empty() {
+                return Err(invalid("shared files need a name"));
+            }
+            let mime_type = Some(file.mime_type)
+                .filter(|mime_type| !mime_type.is_empty())
+                .or_else(|| mime::infer(data, Path::new(&file.name), mime_types));
+            if !mime_type.as_deref().is_some_and(is_permitted) {
+                return Err(invalid(format!(
+                    "sharing `{}` is not permitted: only images, audio, video, text and PDF \
+                     documents can be shared",
+                    file.name
+                )));
+            }
+            items.push(ShareItem::Bytes {
+                data: data.to_vec(),
+                name: file.name,
+                mime_type,
+            });
+        }
+
+        // A lone title is still something to share.
+        if items.is_empty() {
+            if let Some(title) = &self.title {
+                items.push(ShareItem::Text {
+                    text: title.clone(),
+                });
+            }
+        }
+        Ok((items, self.title))
+    }
+}
+
+/// Whether files of `mime_type` may be shared. MIME types are case
+/// insensitive, so they are compared in lowercase like the polyfill does.
+fn is_permitted(mime_type: &str) -> bool {
+    let essence = mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    ["image/", "audio/", "video/", "text/"]
+        .iter()
+        .any(|prefix| essence.starts_with(prefix))
+        || essence == "application/pdf"
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::InvalidInput(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, mime_type: &str, size: u64) -> WebShareFile {
+        WebShareFile {
+            name: name.into(),
+            mime_type: mime_type.into(),
+            size,
+        }
+    }
+
+    fn items(data: WebShareData, body: &[u8]) -> crate::Result<Vec<ShareItem>> {
+        data.into_items(body, &BTreeMap::new())
+            .map(|(items, _)| items)
+    }
+
+    fn rejection(data: WebShareData, body: &[u8]) -> String {
+        match items(data, body) {
+            Err(Error::InvalidInput(message)) => message,
+            other => panic!("expected invalid input, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn text_url_and_files_become_items() {
+        let data = WebShareData {
+            title: Some("Trip".into()),
+            text: Some("Photos from the trip".into()),
+            url: Some("https://example.com/trip/../album?id=1".into()),
+            files: vec![file("a.jpg", "image/jpeg", 3), file("notes.txt", "", 2)],
+        };
+        let (items, title) = data
+            .into_items(b"\xff\xd8\xffhi", &BTreeMap::new())
+            .expect("valid");
+
+        assert_eq!(title.as_deref(), Some("Trip"));
+        assert_eq!(
+            items,
+            vec![
+                ShareItem::Text {
+                    text: "Photos from the trip".into()
+                },
+                ShareItem::Url {
+                    url: "https://example.com/album?id=1".into()
+                },
+                ShareItem::Bytes {
+                    data: b"\xff\xd8\xff".to_vec(),
+                    name: "a.jpg".into(),
+                    mime_type: Some("image/jpeg".into()),
+                },
+                ShareItem::Bytes {
+                    data: b"hi".to_vec(),
+                    name: "notes.txt".into(),
+                    mime_type: Some("text/plain".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_lone_title_is_shared_as_text() {
+        let data = WebShareData {
+            title: Some("Hello".into()),
+            ..WebShareData::default()
+        };
+        assert_eq!(
+            items(data, b"").expect("valid"),
+            vec![ShareItem::Text {
+                text: "Hello".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn empty_data_is_rejected() {
+        assert!(rejection(WebShareData::default(), b"").contains("needs a title"));
+    }
+
+    #[test]
+    fn local_and_invalid_urls_are_rejected() {
+        for url in [
+            "javascript:alert(1)",
+            "file:///etc/passwd",
+            "data:,hi",
+            "not a url",
+        ] {
+            let data = WebShareData {
+                url: Some(url.into()),
+                ..WebShareData::default()
+            };
+            rejection(data, b"");
+        }
+    }
+
+    #[test]
+    fn file_limits_are_enforced() {
+        let data = WebShareData {
+            files: (0..11)
+                .map(|i| file(&format!("{i}.txt"), "text/plain", 0))
+                .collect(),
+            ..WebShareData::default()
+        };
+        assert!(rejection(data, b"").contains("at most 10 files"));
+
+        let data = WebShareData {
+            files: vec![file("big.mp4", "video/mp4", MAX_TOTAL_SIZE + 1)],
+            ..WebShareData::default()
+        };
+        assert!(rejection(data, b"").contains("50 MiB"));
+
+        let data = WebShareData {
+            files: vec![file("a.txt", "text/plain", 4)],
+            ..WebShareData::default()
+        };
+        assert!(rejection(data, b"hi").contains("expected 4 bytes"));
+    }
+
+    #[test]
+    fn overflowing_file_sizes_are_rejected() {
+        let data = WebShareData {
+            files: vec![
+                file("a.txt", "text/plain", u64::MAX),
+                file("b.txt", "text/plain", 1),
+            ],
+            ..WebShareData::default()
+        };
+        assert!(rejection(data, b"").contains("50 MiB"));
+    }
+
+    #[test]
+    fn only_permitted_file_types_are_shared() {
+        let data = WebShareData {
+            files: vec![file("setup.exe", "", 2)],
+            ..WebShareData::default()
+        };
+        assert!(rejection(data, b"MZ").contains("`setup.exe` is not permitted"));
+
+        let data = WebShareData {
+            files: vec![file("page.html", "text/html; charset=utf-8", 2)],
+            ..WebShareData::default()
+        };
+        assert!(items(data, b"<p").is_ok());
+
+        let data = WebShareData {
+            files: vec![file("photo.png", "IMAGE/PNG", 2)],
+            ..WebShareData::default()
+        };
+        assert!(items(data, b"\x89P").is_ok());
+        assert!(is_permitted("Application/PDF"));
+    }
+}
@@ -3,18 +3,29 @@ use std::sync::mpsc;
 use serde::de::DeserializeOwned;
 use tauri::{plugin::PluginApi, AppHandle, Runtime, WebviewWindow};
 
-use crate::models::{ShareFileOptions, ShareTextOptions};
-use crate::Error;
+use crate::config::Config;
+use crate::eml::{self, Attachment};
+use crate::models::{
+    ShareCapabilities, ShareEmailOptions, ShareFileOptions, ShareFilesOptions, ShareHtmlOptions,
+    ShareItem, ShareOptions, ShareTextOptions, ShareUrlOptions, SharedFile,
+};
+use crate::scope::file_path;
+use crate::{Error, ShareExt};
 
 use windows::{
-    core::{Interface, HSTRING},
-    ApplicationModel::DataTransfer::{DataPackage, DataRequestedEventArgs, DataTransferManager},
-    Foundation::TypedEventHandler,
-    Storage::{IStorageItem, StorageFile},
+    core::{w, Interface, HSTRING, PCWSTR},
+    ApplicationModel::DataTransfer::{
+        DataPackage, DataRequestedEventArgs, DataTransferManager, HtmlFormatHelper,
+    },
+    Foundation::{TypedEventHandler, Uri},
+    Storage::{IStorageItem, StorageFile, Streams::RandomAccessStreamReference},
     Win32::{
         Foundation::HWND,
         System::WinRT::{RoGetActivationFactory, RoInitialize, RO_INIT_SINGLETHREADED},
-        UI::Shell::IDataTransferManagerInterop,
+        UI::{
+            Shell::{IDataTransferManagerInterop, ShellExecuteW},
+            WindowsAndMessaging::SW_SHOWNORMAL,
+        },
     },
 };
 use windows_collections::IIterable;
@@ -29,12 +40,13 @@ impl From<windows::core::Error> for Error {
 pub fn init<R: Runtime, C: DeserializeOwned>(
     app: &AppHandle<R>,
     _api: PluginApi<R, C>,
-) -> crate::Result<ShareKit<R>> {
-    Ok(ShareKit::new(app.clone()))
+    _config: Config,
+) -> crate::Result<NativeBackend<R>> {
+    Ok(NativeBackend::new(app.clone()))
 }
 
-/// Access to the share APIs.
-pub struct ShareKit<R: Runtime> {
+/// Built-in backend using the Windows share UI.
+pub struct NativeBackend<R: Runtime> {
     app: AppHandle<R>,
 }
 
@@ -112,17 +124,32 @@ where
     }
 }
 
-impl<R: Runtime> ShareKit<R> {
+impl<R: Runtime> NativeBackend<R> {
     pub const fn new(app: AppHandle<R>) -> Self {
         Self { app }
     }
 
+    /// The share UI is always shown in the same place, and reports whether
+    /// the share completed.
+    #[allow(clippy::unused_self)] // same signature as the backends that depend on their config
+    pub const fn capabilities(&self) -> ShareCapabilities {
+        ShareCapabilities {
+            text: true,
+            files: true,
+            multiple_files: true,
+            urls: true,
+            reports_result: true,
+            anchors_position: false,
+            reports_dismissal: true,
+        }
+    }
+
     /// Opens the native share UI to share text content.
     pub async fn share_text(
         &self,
         window: WebviewWindow<R>,
         text: String,
-        _options: ShareTextOptions,
+        options: ShareTextOptions,
     ) -> crate::Result<()> {
         let app_name = self.app.package_info().name.clone();
 
@@ -132,11 +159,12 @@ impl<R: Runtime> ShareKit<R> {
                 .hwnd()
                 .map_err(|e| Error::WindowsApi(e.to_string()))?;
             let content = HSTRING::from(text);
-            let app_name = HSTRING::from(app_name);
+            let title = options.title.or(options.subject).unwrap_or(app_name);
+            let title = HSTRING::from(title);
 
             present_share_ui(hwnd, move |data| {
                 let props = data.Properties()?;
-                props.SetTitle(&app_name)?;
+                props.SetTitle(&title)?;
                 props.SetDescription(&content)?;
                 data.SetText(&content)?;
                 Ok(())
@@ -146,6 +174,76 @@ impl<R: Runtime> ShareKit<R> {
         .map_err(|e| Error::WindowsApi(format!("blocking task: {e}")))?
     }
 
+    /// Opens the native share UI to share formatted text, with the plain
+    /// text for targets that cannot show HTML.
+    pub async fn share_html(
+        &self,
+        window: WebviewWindow<R>,
+        html: String,
+        text: String,
+        options: ShareHtmlOptions,
+    ) -> crate::Result<()> {
+        let app_name = self.app.package_info().name.clone();
+
+        tokio::task::spawn_blocking(move || -> crate::Result<()> {
+            init_apartment();
+            let hwnd = window
+                .hwnd()
+                .map_err(|e| Error::WindowsApi(e.to_string()))?;
+            let title = options.title.or(options.subject).unwrap_or(app_name);
+            let title = HSTRING::from(title);
+            let text = HSTRING::from(text);
+            let html = HSTRING::from(html);
+
+            present_share_ui(hwnd, move |data| {
+                let props = data.Properties()?;
+                props.SetTitle(&title)?;
+                props.SetDescription(&text)?;
+                data.SetText(&text)?;
+                data.SetHtmlFormat(&HtmlFormatHelper::CreateHtmlFormat(&html)?)
+            })
+        })
+        .await
+        .map_err(|e| Error::WindowsApi(format!("blocking task: {e}")))?
+    }
+
+    /// Opens the native share UI to share a link, with its title,
+    /// description and thumbnail in the preview.
+    pub async fn share_url(
+        &self,
+        window: WebviewWindow<R>,
+        url: String,
+        options: ShareUrlOptions,
+    ) -> crate::Result<()> {
+        let app_name = self.app.package_info().name.clone();
+
+        tokio::task::spawn_blocking(move || -> crate::Result<()> {
+            init_apartment();
+            let hwnd = window
+                .hwnd()
+                .map_err(|e| Error::WindowsApi(e.to_string()))?;
+            let title = HSTRING::from(options.title.as_deref().unwrap_or(&app_name));
+            let description = HSTRING::from(options.description.as_deref().unwrap_or(&url));
+            let thumbnail = options
+                .thumbnail
+                .map(|thumbnail| HSTRING::from(file_path(&thumbnail).as_os_str()));
+            let link = HSTRING::from(url);
+
+            present_share_ui(hwnd, move |data| {
+                let props = data.Properties()?;
+                props.SetTitle(&title)?;
+                props.SetDescription(&description)?;
+                if let Some(path) = &thumbnail {
+                    let file = StorageFile::GetFileFromPathAsync(path)?.get()?;
+                    props.SetThumbnail(&RandomAccessStreamReference::CreateFromFile(&file)?)?;
+                }
+                data.SetWebLink(&Uri::CreateUri(&link)?)
+            })
+        })
+        .await
+        .map_err(|e| Error::WindowsApi(format!("blocking task: {e}")))?
+    }
+
     /// Opens the native share UI to share a file.
     pub async fn share_file(
         &self,
@@ -153,26 +251,154 @@ impl<R: Runtime> ShareKit<R> {
         url: String,
         options: ShareFileOptions,
     ) -> crate::Result<()> {
+        self.share_storage_files(window, vec![url], options.title)
+            .await
+    }
+
+    /// Opens the native share UI to share several files at once.
+    pub async fn share_files(
+        &self,
+        window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareFilesOptions,
+    ) -> crate::Result<()> {
+        let paths = files.into_iter().map(|file| file.url).collect();
+        self.share_storage_files(window, paths, options.title).await
+    }
+
+    async fn share_storage_files(
+        &self,
+        window: WebviewWindow<R>,
+        paths: Vec<String>,
+        title: Option<String>,
+    ) -> crate::Result<()> {
+        let app_name = self.app.package_info().name.clone();
+
+        tokio::task::spawn_blocking(move || -> crate::Result<()> {
+            init_apartment();
+            let hwnd = window
+                .hwnd()
+                .map_err(|e| Error::WindowsApi(e.to_string()))?;
+            let paths: Vec<HSTRING> = paths.into_iter().map(HSTRING::from).collect();
+            let title = HSTRING::from(title.as_deref().unwrap_or(&app_name));
+
+            present_share_ui(hwnd, move |data| {
+                // Title and Description are required for Windows share to work properly
+                let props = data.Properties()?;
+                props.SetTitle(&title)?;
+                props.SetDescription(&title)?;
+
+                set_storage_items(data, &paths)
+            })
+        })
+        .await
+        .map_err(|e| Error::WindowsApi(format!("blocking task: {e}")))?
+    }
+
+    /// Opens a new email in the default mail client, written as a draft
+    /// `.eml` message with the files attached, since the share UI cannot
+    /// address one.
+    pub async fn share_email(
+        &self,
+        _window: WebviewWindow<R>,
+        files: Vec<SharedFile>,
+        options: ShareEmailOptions,
+    ) -> crate::Result<()> {
+        let staging = self.app.share().staging().clone();
+
+        tokio::task::spawn_blocking(move || -> crate::Result<()> {
+            let attachments = files
+                .iter()
+                .map(Attachment::read)
+                .collect::<crate::Result<Vec<_>>>()?;
+            let message = eml::build(&options, &attachments);
+            let path = staging.stage_bytes(eml::FILE_NAME, &message)?;
+
+            init_apartment();
+            let file = HSTRING::from(path.as_os_str());
+            let result = unsafe {
+                ShellExecuteW(
+                    None,
+                    w!("open"),
+                    &file,
+                    PCWSTR::null(),
+                    PCWSTR::null(),
+                    SW_SHOWNORMAL,
+                )
+            };
+            // Values up to 32 are error codes.
+            if result.0 as isize > 32 {
+                Ok(())
+            } else {
+                Err(Error::WindowsApi(format!(
+                    "no application opened {}",
+                    path.display()
+                )))
+            }
+        })
+        .await
+        .map_err(|e| Error::WindowsApi(format!("blocking task: {e}")))?
+    }
+
+    /// Opens the native share UI with a mix of text, links, HTML and files.
+    pub async fn share(
+        &self,
+        window: WebviewWindow<R>,
+        items: Vec<ShareItem>,
+        options: ShareOptions,
+    ) -> crate::Result<()> {
+        let staging = self.app.share().staging().clone();
         let app_name = self.app.package_info().name.clone();
 
         tokio::task::spawn_blocking(move || -> crate::Result<()> {
+            let items = staging.stage_items(items)?;
             init_apartment();
             let hwnd = window
                 .hwnd()
                 .map_err(|e| Error::WindowsApi(e.to_string()))?;
-            let path = HSTRING::from(url);
             let title = HSTRING::from(options.title.as_deref().unwrap_or(&app_name));
 
+            // A DataPackage holds one value per format: the first URL becomes
+            // the web link, further URLs are appended to the text.
+            let mut texts = Vec::new();
+            let mut web_link = None;
+            let mut html = None;
+            let mut paths = Vec::new();
+            for item in items {
+                match item {
+                    ShareItem::Text { text } => texts.push(text),
+                    ShareItem::Url { url } if web_link.is_none() => {
+                        web_link = Some(HSTRING::from(url));
+                    }
+                    ShareItem::Url { url } => texts.push(url),
+                    ShareItem::File { url, .. } => paths.push(HSTRING::from(url)),
+                    ShareItem::Html { html: fragment } => {
+                        html.get_or_insert_with(String::new).push_str(&fragment);
+                    }
+                    ShareItem::Bytes { .. } => {}
+                }
+            }
+            let text = (!texts.is_empty()).then(|| HSTRING::from(texts.join("\n")));
+            let html = html.map(HSTRING::from);
+
             present_share_ui(hwnd, move |data| {
                 // Title and Description are required for Windows share to work properly
                 let props = data.Properties()?;
                 props.SetTitle(&title)?;
                 props.SetDescription(&title)?;
 
-                let file = StorageFile::GetFileFromPathAsync(&path)?.get()?;
-                let storage_item = file.cast::<IStorageItem>()?;
-                let storage_items: IIterable<IStorageItem> = vec![Some(storage_item)].into();
-                data.SetStorageItemsReadOnly(&storage_items)?;
+                if let Some(text) = &text {
+                    data.SetText(text)?;
+                }
+                if let Some(link) = &web_link {
+                    data.SetWebLink(&Uri::CreateUri(link)?)?;
+                }
+                if let Some(html) = &html {
+                    data.SetHtmlFormat(&HtmlFormatHelper::CreateHtmlFormat(html)?)?;
+                }
+                if !paths.is_empty() {
+                    set_storage_items(data, &paths)?;
+                }
                 Ok(())
             })
         })
@@ -181,6 +407,19 @@ impl<R: Runtime> ShareKit<R> {
     }
 }
 
+/// Resolves `paths` to storage files and attaches them to the package.
+fn set_storage_items(data: &DataPackage, paths: &[HSTRING]) -> windows::core::Result<()> {
+    let storage_items = paths
+        .iter()
+        .map(|path| {
+            let file = StorageFile::GetFileFromPathAsync(path)?.get()?;
+            Ok(Some(file.cast::<IStorageItem>()?))
+        })
+        .collect::<windows::core::Result<Vec<_>>>()?;
+    let storage_items: IIterable<IStorageItem> = storage_items.into();
+    data.SetStorageItemsReadOnly(&storage_items)
+}
+
 /// Initializes the `WinRT` apartment for the current thread.
 /// Safe to call repeatedly — subsequent calls return `S_FALSE` / `RPC_E_CHANGED_MODE`
 /// which we intentionally ignore.